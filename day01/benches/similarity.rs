@@ -0,0 +1,37 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::hint::black_box;
+
+use day01::similarity::SimilarityStrategy;
+
+// Generate a sorted list of location ids with plenty of repeated ids
+fn generate_locations(n: usize, seed: u64) -> Vec<i32> {
+    let mut state = seed;
+    let mut locations: Vec<i32> = (0..n)
+        .map(|_| {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % (n as u64 / 2 + 1)) as i32
+        })
+        .collect();
+    locations.sort();
+    locations
+}
+
+fn bench_similarity_score(c: &mut Criterion) {
+    let mut group = c.benchmark_group("similarity_score");
+    for n in [1_000, 100_000] {
+        let left_locations = generate_locations(n, 0x5eed);
+        let right_locations = generate_locations(n, 0xcafe);
+        for strategy in [SimilarityStrategy::Hash, SimilarityStrategy::SortMerge] {
+            group.bench_with_input(BenchmarkId::new(format!("{:?}", strategy), n), &n, |b, _| {
+                b.iter(|| strategy.compute(black_box(&left_locations), black_box(&right_locations)))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_similarity_score);
+criterion_main!(benches);
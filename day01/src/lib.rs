@@ -0,0 +1,4 @@
+pub mod metrics;
+pub mod pairing;
+pub mod report;
+pub mod similarity;
@@ -1,22 +1,76 @@
 use std::fs::File;
 use std::io::{self, BufRead};
-use std::path::Path;
-use anyhow::Result;
-use counter::Counter;
+use std::path::{Path, PathBuf};
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+
+use day01::metrics::Metric;
+use day01::pairing::PairingPolicy;
+use day01::report::{self, Breakdown, ReportFormat, SortKey};
+use day01::similarity::SimilarityStrategy;
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// Puzzle input with a left and right location id on each line
+    #[arg(long, default_value = "puzzle_input.tsv")]
+    input: PathBuf,
+
+    /// Metric to compare the left and right location lists, can be given multiple times
+    #[arg(long = "metric", value_enum, default_values_t = [Metric::TotalDistance])]
+    metrics: Vec<Metric>,
+
+    /// How to pair the lists when they differ in length: error, truncate, pad[=<id>] or assign
+    #[arg(long, default_value = "error")]
+    pairing: PairingPolicy,
+
+    /// Strategy to compute the similarity score
+    #[arg(long, value_enum, default_value_t = SimilarityStrategy::SortMerge)]
+    similarity: SimilarityStrategy,
+
+    /// Print a per-id breakdown instead of the totals
+    #[arg(long, value_enum)]
+    report: Option<Breakdown>,
+
+    /// Order of the rows in the breakdown
+    #[arg(long, value_enum, default_value_t = SortKey::Id)]
+    sort_by: SortKey,
+
+    /// Output format of the breakdown
+    #[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+    format: ReportFormat,
+}
 
 fn main() -> Result<()> {
-    let path: &str = "puzzle_input.tsv";
-    let (mut left_locations, mut right_locations) = read_location_ids(path)?;
+    let args = Args::parse();
+    let (mut left_locations, mut right_locations) = read_location_ids(&args.input)?;
+
+    if let Some(breakdown) = args.report {
+        left_locations.sort();
+        right_locations.sort();
+        print!("{}", format_breakdown(breakdown, &left_locations, &right_locations, &args)?);
+        return Ok(());
+    }
+
+    if left_locations.len() != right_locations.len() {
+        println!(
+            "Location lists differ in length ({} left, {} right ids)",
+            left_locations.len(), right_locations.len()
+        );
+    }
+    if args.metrics.iter().any(Metric::requires_pairing) {
+        println!("Pairing policy: {}", args.pairing);
+    }
+
+    // Print the requested metrics
+    for metric in &args.metrics {
+        println!("{}: {}", metric, metric.compute(&left_locations, &right_locations, args.pairing)?);
+    }
 
     // Sort the lists
     left_locations.sort();
     right_locations.sort();
 
-    let total_distance: i32 = compute_total_distance(&left_locations, &right_locations);
-    let similarity_score: i32 = compute_similarity_score(&left_locations, &right_locations);
-
-    // Print the total distance
-    println!("Total distance: {}", total_distance);
+    let similarity_score: i64 = args.similarity.compute(&left_locations, &right_locations);
 
     // Print the similarity score
     println!("Similarity score: {}", similarity_score);
@@ -24,39 +78,34 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-// Compute the total distance between two sets of locations
-fn compute_total_distance(left_locations: &Vec<i32>, right_locations: &Vec<i32>) -> i32 {
-    let distances: Vec<i32> = left_locations.iter()
-        .zip(right_locations.iter())
-        .map(|(left, right)| (left - right).abs())
-        .collect();
-    distances.iter().sum()
-}
-
-// Compute the similarity score between two sets of location ids
-fn compute_similarity_score(left_locations: &Vec<i32>, right_locations: &Vec<i32>) -> i32 {
-    let left_counter: Counter<_> = left_locations.iter().collect();
-    let right_counter: Counter<_> = right_locations.iter().collect();
-    _compute_similarity_score_from_counters(left_counter, right_counter)
-}
-
-// Compute the similarity score between two sets of location ids from their counters
-fn _compute_similarity_score_from_counters(left_counter: Counter<&i32>, right_counter: Counter<&i32>) -> i32 {
-    let mut score: i32 = 0;
-    for (id, left_count) in left_counter.into_iter() {
-        if let Some(&right_count) = right_counter.get(id) {
-            score += id * (left_count * right_count) as i32;
+// Format the breakdown of the sorted location lists
+fn format_breakdown(breakdown: Breakdown, left_locations: &[i32], right_locations: &[i32], args: &Args) -> Result<String> {
+    match breakdown {
+        Breakdown::Pairs => {
+            let mut pairs = report::pair_contributions(left_locations, right_locations, args.pairing)?;
+            report::sort_pair_contributions(&mut pairs, args.sort_by);
+            report::format_pair_contributions(&pairs, args.format)
+        }
+        Breakdown::Ids => {
+            let mut ids = report::id_contributions(left_locations, right_locations);
+            report::sort_id_contributions(&mut ids, args.sort_by);
+            report::format_id_contributions(&ids, args.format)
         }
     }
-    score
 }
 
-// Split a line into a tuple of location ids
-fn split_location_ids(line: String) -> (i32, i32) {
+// Split a line into a tuple of location ids.
+// A line with a single id belongs to the right list if it is indented, otherwise to the left list.
+fn split_location_ids(line: &str) -> Result<(Option<i32>, Option<i32>)> {
     let split: Vec<&str> = line.split_whitespace().collect();
-    let left_id: i32 = split[0].parse().unwrap();
-    let right_id: i32 = split[1].parse().unwrap();
-    (left_id, right_id)
+    let parse = |s: &str| s.parse::<i32>().with_context(|| format!("invalid location id '{}'", s));
+    match split[..] {
+        [] => Ok((None, None)),
+        [id] if line.starts_with(char::is_whitespace) => Ok((None, Some(parse(id)?))),
+        [id] => Ok((Some(parse(id)?), None)),
+        [left_id, right_id] => Ok((Some(parse(left_id)?), Some(parse(right_id)?))),
+        _ => bail!("expected at most two location ids in line '{}'", line),
+    }
 }
 
 // Read left and right location ids from a file
@@ -70,12 +119,10 @@ where
     let mut right_locations: Vec<i32> = Vec::new();
 
     for line in lines {
-        if let Ok(ip) = line {
-            let (left_id, right_id) = split_location_ids(ip);
-            left_locations.push(left_id);
-            right_locations.push(right_id);
-        }
+        let (left_id, right_id) = split_location_ids(&line?)?;
+        left_locations.extend(left_id);
+        right_locations.extend(right_id);
     }
 
     Ok((left_locations, right_locations))
-}
\ No newline at end of file
+}
@@ -0,0 +1,248 @@
+use std::fmt;
+use clap::ValueEnum;
+use anyhow::Result;
+use counter::Counter;
+
+use crate::pairing::PairingPolicy;
+
+// Metrics to compare the left and right location lists.
+// The lists are given in input order, so row i of the puzzle input pairs left[i] with right[i].
+// Metrics which pair the lists by rank sort their own copies of the lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Metric {
+    // Sum of absolute differences after pairing the sorted lists (the puzzle answer)
+    TotalDistance,
+    // Sum of squared differences after pairing the sorted lists
+    SquaredError,
+    // Earth mover's (Wasserstein-1) distance between the lists as distributions, lengths may differ
+    EarthMovers,
+    // Kendall tau-b rank correlation between the rows of the input
+    KendallTau,
+    // Spearman rank correlation between the rows of the input
+    Spearman,
+    // Jaccard index of the lists as multisets, lengths may differ
+    Jaccard,
+}
+
+impl Metric {
+    // Compute the metric between the left and right location lists.
+    // Metrics which compare the lists id by id first pair them up according to the pairing policy.
+    pub fn compute(&self, left_locations: &[i32], right_locations: &[i32], policy: PairingPolicy) -> Result<f64> {
+        if !self.requires_pairing() {
+            return Ok(match self {
+                Metric::EarthMovers => {
+                    compute_earth_movers_distance(&sorted(left_locations), &sorted(right_locations))
+                }
+                Metric::Jaccard => compute_jaccard_index(left_locations, right_locations),
+                _ => unreachable!(),
+            });
+        }
+
+        let (left_locations, right_locations) = policy.pair(left_locations, right_locations)?;
+        Ok(match self {
+            Metric::TotalDistance => {
+                compute_total_distance(&sorted(&left_locations), &sorted(&right_locations)) as f64
+            }
+            Metric::SquaredError => {
+                compute_squared_error(&sorted(&left_locations), &sorted(&right_locations)) as f64
+            }
+            Metric::KendallTau => compute_kendall_tau(&left_locations, &right_locations),
+            Metric::Spearman => compute_spearman_correlation(&left_locations, &right_locations),
+            _ => unreachable!(),
+        })
+    }
+
+    // Whether the metric compares the lists id by id, so both lists need the same length
+    pub fn requires_pairing(&self) -> bool {
+        !matches!(self, Metric::EarthMovers | Metric::Jaccard)
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Metric::TotalDistance => "Total distance",
+            Metric::SquaredError => "Squared error",
+            Metric::EarthMovers => "Earth mover's distance",
+            Metric::KendallTau => "Kendall tau",
+            Metric::Spearman => "Spearman correlation",
+            Metric::Jaccard => "Jaccard index",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+fn sorted(locations: &[i32]) -> Vec<i32> {
+    let mut locations = locations.to_vec();
+    locations.sort();
+    locations
+}
+
+// Compute the total distance between two sorted lists of locations
+pub fn compute_total_distance(left_locations: &[i32], right_locations: &[i32]) -> i64 {
+    left_locations.iter()
+        .zip(right_locations.iter())
+        .map(|(&left, &right)| (left as i64 - right as i64).abs())
+        .sum()
+}
+
+// Compute the sum of squared differences between two sorted lists of locations
+fn compute_squared_error(left_locations: &[i32], right_locations: &[i32]) -> i64 {
+    left_locations.iter()
+        .zip(right_locations.iter())
+        .map(|(&left, &right)| (left as i64 - right as i64).pow(2))
+        .sum()
+}
+
+// Compute the earth mover's distance between two sorted lists of locations.
+// This is the area between the empirical cumulative distributions of both lists.
+fn compute_earth_movers_distance(left_locations: &[i32], right_locations: &[i32]) -> f64 {
+    if left_locations.is_empty() || right_locations.is_empty() {
+        return f64::NAN;
+    }
+
+    let n_left = left_locations.len() as f64;
+    let n_right = right_locations.len() as f64;
+    let (mut i, mut j) = (0, 0);
+    let mut previous = left_locations[0].min(right_locations[0]);
+    let mut distance: f64 = 0.0;
+
+    while i < left_locations.len() || j < right_locations.len() {
+        let current = match (left_locations.get(i), right_locations.get(j)) {
+            (Some(&left), Some(&right)) => left.min(right),
+            (Some(&left), None) => left,
+            (None, Some(&right)) => right,
+            (None, None) => unreachable!(),
+        };
+        let cdf_difference = (i as f64 / n_left - j as f64 / n_right).abs();
+        distance += cdf_difference * (current as i64 - previous as i64) as f64;
+
+        while i < left_locations.len() && left_locations[i] == current {
+            i += 1;
+        }
+        while j < right_locations.len() && right_locations[j] == current {
+            j += 1;
+        }
+        previous = current;
+    }
+
+    distance
+}
+
+// Compute the Kendall tau-b rank correlation between the rows of the location lists
+fn compute_kendall_tau(left_locations: &[i32], right_locations: &[i32]) -> f64 {
+    let rows: Vec<(i32, i32)> = left_locations.iter().copied().zip(right_locations.iter().copied()).collect();
+    let (mut concordant, mut discordant) = (0_i64, 0_i64);
+    let (mut left_ties, mut right_ties) = (0_i64, 0_i64);
+
+    for (i, &(left_i, right_i)) in rows.iter().enumerate() {
+        for &(left_j, right_j) in &rows[i + 1..] {
+            let left_order = left_i.cmp(&left_j);
+            let right_order = right_i.cmp(&right_j);
+            match (left_order.is_eq(), right_order.is_eq()) {
+                (true, true) => {}
+                (true, false) => left_ties += 1,
+                (false, true) => right_ties += 1,
+                (false, false) if left_order == right_order => concordant += 1,
+                (false, false) => discordant += 1,
+            }
+        }
+    }
+
+    let denominator = (((concordant + discordant + left_ties) * (concordant + discordant + right_ties)) as f64).sqrt();
+    (concordant - discordant) as f64 / denominator
+}
+
+// Compute the Spearman rank correlation between the rows of the location lists
+fn compute_spearman_correlation(left_locations: &[i32], right_locations: &[i32]) -> f64 {
+    let n = left_locations.len().min(right_locations.len());
+    let left_ranks = ranks(&left_locations[..n]);
+    let right_ranks = ranks(&right_locations[..n]);
+    pearson_correlation(&left_ranks, &right_ranks)
+}
+
+// Rank the values starting at 1, where tied values get the average of their ranks
+fn ranks(values: &[i32]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by_key(|&i| values[i]);
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start;
+        while end + 1 < order.len() && values[order[end + 1]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end) as f64 / 2.0 + 1.0;
+        for &i in &order[start..=end] {
+            ranks[i] = rank;
+        }
+        start = end + 1;
+    }
+
+    ranks
+}
+
+fn pearson_correlation(x: &[f64], y: &[f64]) -> f64 {
+    let n = x.len() as f64;
+    let mean_x = x.iter().sum::<f64>() / n;
+    let mean_y = y.iter().sum::<f64>() / n;
+
+    let mut covariance = 0.0;
+    let mut variance_x = 0.0;
+    let mut variance_y = 0.0;
+    for (xi, yi) in x.iter().zip(y.iter()) {
+        covariance += (xi - mean_x) * (yi - mean_y);
+        variance_x += (xi - mean_x).powi(2);
+        variance_y += (yi - mean_y).powi(2);
+    }
+
+    covariance / (variance_x * variance_y).sqrt()
+}
+
+// Compute the Jaccard index of the location lists as multisets:
+// the size of their intersection divided by the size of their union
+fn compute_jaccard_index(left_locations: &[i32], right_locations: &[i32]) -> f64 {
+    let left_counter: Counter<i32> = left_locations.iter().copied().collect();
+    let right_counter: Counter<i32> = right_locations.iter().copied().collect();
+
+    let intersection: usize = (left_counter.clone() & right_counter.clone()).values().sum();
+    let union: usize = (left_counter | right_counter).values().sum();
+    intersection as f64 / union as f64
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    const LEFT: [i32; 6] = [3, 4, 2, 1, 3, 3];
+    const RIGHT: [i32; 6] = [4, 3, 5, 3, 9, 3];
+
+    #[test_case(Metric::TotalDistance, 11.0)]
+    #[test_case(Metric::SquaredError, 35.0)]
+    #[test_case(Metric::EarthMovers, 11.0 / 6.0)]
+    #[test_case(Metric::Jaccard, 4.0 / 8.0)]
+    fn test_metric(metric: Metric, expected: f64) {
+        let actual = metric.compute(&LEFT, &RIGHT, PairingPolicy::Error).unwrap();
+        assert!((actual - expected).abs() < 1e-12, "{}: {} != {}", metric, actual, expected);
+    }
+
+    #[test_case(Metric::KendallTau)]
+    #[test_case(Metric::Spearman)]
+    fn test_rank_correlation_of_identical_lists(metric: Metric) {
+        assert!((metric.compute(&LEFT, &LEFT, PairingPolicy::Error).unwrap() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_earth_movers_distance_unequal_lengths() {
+        // Moving half of the mass from 0 to 2
+        assert_eq!(compute_earth_movers_distance(&[0, 2], &[2]), 1.0);
+    }
+
+    #[test]
+    fn test_total_distance_beyond_i32() {
+        assert_eq!(compute_total_distance(&[-2000000000, 0], &[2000000000, 1]), 4000000001);
+    }
+}
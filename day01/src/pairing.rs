@@ -0,0 +1,150 @@
+use std::fmt;
+use std::str::FromStr;
+use anyhow::{anyhow, bail, Result};
+
+// Policy to pair up the left and right location lists when they differ in length
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PairingPolicy {
+    // Refuse to pair lists of unequal length
+    Error,
+    // Ignore the rows beyond the end of the shorter list
+    Truncate,
+    // Pad the shorter list with the given location id
+    Pad(i32),
+    // Assign each id of the shorter list to a distinct id of the longer list,
+    // such that the total distance between the assigned ids is minimal
+    Assign,
+}
+
+impl PairingPolicy {
+    // Pair the location lists so both have the same length, keeping the order of the rows
+    pub fn pair(&self, left_locations: &[i32], right_locations: &[i32]) -> Result<(Vec<i32>, Vec<i32>)> {
+        let (n_left, n_right) = (left_locations.len(), right_locations.len());
+        if n_left == n_right {
+            return Ok((left_locations.to_vec(), right_locations.to_vec()));
+        }
+
+        match self {
+            PairingPolicy::Error => bail!(
+                "location lists differ in length ({} left, {} right ids)", n_left, n_right
+            ),
+            PairingPolicy::Truncate => {
+                let n = n_left.min(n_right);
+                Ok((left_locations[..n].to_vec(), right_locations[..n].to_vec()))
+            }
+            PairingPolicy::Pad(id) => {
+                let n = n_left.max(n_right);
+                let mut left_locations = left_locations.to_vec();
+                let mut right_locations = right_locations.to_vec();
+                left_locations.resize(n, *id);
+                right_locations.resize(n, *id);
+                Ok((left_locations, right_locations))
+            }
+            PairingPolicy::Assign => {
+                if n_left < n_right {
+                    Ok((left_locations.to_vec(), assign_partners(left_locations, right_locations)))
+                } else {
+                    Ok((assign_partners(right_locations, left_locations), right_locations.to_vec()))
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for PairingPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PairingPolicy::Error => write!(f, "error"),
+            PairingPolicy::Truncate => write!(f, "truncate"),
+            PairingPolicy::Pad(id) => write!(f, "pad={}", id),
+            PairingPolicy::Assign => write!(f, "assign"),
+        }
+    }
+}
+
+impl FromStr for PairingPolicy {
+    type Err = anyhow::Error;
+
+    // Parse "error", "truncate", "pad", "pad=<id>" or "assign"
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once('=') {
+            None if s == "error" => Ok(PairingPolicy::Error),
+            None if s == "truncate" => Ok(PairingPolicy::Truncate),
+            None if s == "pad" => Ok(PairingPolicy::Pad(0)),
+            None if s == "assign" => Ok(PairingPolicy::Assign),
+            Some(("pad", id)) => Ok(PairingPolicy::Pad(id.parse()?)),
+            _ => Err(anyhow!("unknown pairing policy '{}', expected error, truncate, pad[=<id>] or assign", s)),
+        }
+    }
+}
+
+// Find a partner in the longer list for each id of the shorter list, in the order of the shorter list.
+// In one dimension there is an optimal assignment which preserves the order of the sorted lists,
+// so a dynamic program over both sorted lists finds it.
+fn assign_partners(shorter: &[i32], longer: &[i32]) -> Vec<i32> {
+    let mut order: Vec<usize> = (0..shorter.len()).collect();
+    order.sort_by_key(|&i| shorter[i]);
+    let mut sorted_longer = longer.to_vec();
+    sorted_longer.sort();
+
+    // cost[i][k] is the minimal cost of assigning the i smallest ids of the shorter list
+    // to the i + k smallest ids of the longer list, skipping k of them
+    let n_skips = longer.len() - shorter.len();
+    let mut cost: Vec<Vec<i64>> = vec![vec![0; n_skips + 1]; shorter.len() + 1];
+    for i in 1..=shorter.len() {
+        let id = shorter[order[i - 1]] as i64;
+        for k in 0..=n_skips {
+            let take = cost[i - 1][k] + (id - sorted_longer[i + k - 1] as i64).abs();
+            cost[i][k] = if k > 0 { take.min(cost[i][k - 1]) } else { take };
+        }
+    }
+
+    // Walk back through the table to find which id of the longer list each id was assigned to
+    let mut partners: Vec<i32> = vec![0; shorter.len()];
+    let (mut i, mut k) = (shorter.len(), n_skips);
+    while i > 0 {
+        if k > 0 && cost[i][k] == cost[i][k - 1] {
+            k -= 1;
+        } else {
+            partners[order[i - 1]] = sorted_longer[i + k - 1];
+            i -= 1;
+        }
+    }
+
+    partners
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(PairingPolicy::Truncate, vec![3, 4], vec![4, 3])]
+    #[test_case(PairingPolicy::Pad(0), vec![3, 4, 0], vec![4, 3, 5])]
+    #[test_case(PairingPolicy::Assign, vec![3, 4], vec![3, 4])]
+    fn test_pair_unequal_lengths(policy: PairingPolicy, expected_left: Vec<i32>, expected_right: Vec<i32>) {
+        let (left, right) = policy.pair(&[3, 4], &[4, 3, 5]).unwrap();
+        assert_eq!(left, expected_left);
+        assert_eq!(right, expected_right);
+    }
+
+    #[test]
+    fn test_pair_unequal_lengths_error() {
+        assert!(PairingPolicy::Error.pair(&[3, 4], &[4, 3, 5]).is_err());
+        assert!(PairingPolicy::Error.pair(&[3, 4], &[4, 3]).is_ok());
+    }
+
+    #[test]
+    fn test_assign_partners_skips_outliers() {
+        assert_eq!(assign_partners(&[10, 1], &[100, 2, 11, -50]), vec![11, 2]);
+    }
+
+    #[test_case("error", PairingPolicy::Error)]
+    #[test_case("pad", PairingPolicy::Pad(0))]
+    #[test_case("pad=-7", PairingPolicy::Pad(-7))]
+    #[test_case("assign", PairingPolicy::Assign)]
+    fn test_parse_pairing_policy(s: &str, expected: PairingPolicy) {
+        assert_eq!(s.parse::<PairingPolicy>().unwrap(), expected);
+    }
+}
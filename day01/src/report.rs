@@ -0,0 +1,201 @@
+use std::fmt::Write;
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::pairing::PairingPolicy;
+
+// Breakdown to report on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Breakdown {
+    // Each pair of sorted location ids and their distance
+    Pairs,
+    // Each location id and its contribution to the similarity score
+    Ids,
+}
+
+// Order of the rows in a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    // Ascending by location id
+    Id,
+    // Descending by contribution to the total distance or similarity score
+    Contribution,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+// Contribution of a pair of location ids to the total distance
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PairContribution {
+    pub left: i32,
+    pub right: i32,
+    pub distance: i64,
+}
+
+// Contribution of a location id to the similarity score
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IdContribution {
+    pub id: i32,
+    pub left_count: usize,
+    pub right_count: usize,
+    pub contribution: i64,
+}
+
+// Pair the sorted location lists and compute the distance of each pair
+pub fn pair_contributions(
+    left_locations: &[i32],
+    right_locations: &[i32],
+    policy: PairingPolicy,
+) -> Result<Vec<PairContribution>> {
+    let (mut left_locations, mut right_locations) = policy.pair(left_locations, right_locations)?;
+    left_locations.sort();
+    right_locations.sort();
+
+    Ok(left_locations.into_iter()
+        .zip(right_locations)
+        .map(|(left, right)| PairContribution { left, right, distance: (left as i64 - right as i64).abs() })
+        .collect())
+}
+
+// Count each location id in both sorted lists and compute its contribution to the similarity score.
+// Ids which only occur in one of the lists are included with a contribution of zero.
+pub fn id_contributions(left_locations: &[i32], right_locations: &[i32]) -> Vec<IdContribution> {
+    let mut contributions: Vec<IdContribution> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < left_locations.len() || j < right_locations.len() {
+        let id = match (left_locations.get(i), right_locations.get(j)) {
+            (Some(&left), Some(&right)) => left.min(right),
+            (Some(&left), None) => left,
+            (None, Some(&right)) => right,
+            (None, None) => unreachable!(),
+        };
+        let left_count = left_locations[i..].iter().take_while(|&&left| left == id).count();
+        let right_count = right_locations[j..].iter().take_while(|&&right| right == id).count();
+        contributions.push(IdContribution {
+            id,
+            left_count,
+            right_count,
+            contribution: id as i64 * (left_count * right_count) as i64,
+        });
+        i += left_count;
+        j += right_count;
+    }
+
+    contributions
+}
+
+pub fn sort_pair_contributions(pairs: &mut [PairContribution], key: SortKey) {
+    match key {
+        SortKey::Id => pairs.sort_by_key(|pair| (pair.left, pair.right)),
+        SortKey::Contribution => pairs.sort_by(|a, b| b.distance.cmp(&a.distance).then(a.left.cmp(&b.left))),
+    }
+}
+
+pub fn sort_id_contributions(ids: &mut [IdContribution], key: SortKey) {
+    match key {
+        SortKey::Id => ids.sort_by_key(|id| id.id),
+        SortKey::Contribution => ids.sort_by(|a, b| b.contribution.cmp(&a.contribution).then(a.id.cmp(&b.id))),
+    }
+}
+
+// Format the pair contributions in the given format
+pub fn format_pair_contributions(pairs: &[PairContribution], format: ReportFormat) -> Result<String> {
+    let rows: Vec<[String; 3]> = pairs.iter()
+        .map(|pair| [pair.left.to_string(), pair.right.to_string(), pair.distance.to_string()])
+        .collect();
+    format_rows(pairs, &["left", "right", "distance"], &rows, format)
+}
+
+// Format the id contributions in the given format
+pub fn format_id_contributions(ids: &[IdContribution], format: ReportFormat) -> Result<String> {
+    let rows: Vec<[String; 4]> = ids.iter()
+        .map(|id| [
+            id.id.to_string(),
+            id.left_count.to_string(),
+            id.right_count.to_string(),
+            id.contribution.to_string(),
+        ])
+        .collect();
+    format_rows(ids, &["id", "left_count", "right_count", "contribution"], &rows, format)
+}
+
+fn format_rows<T: Serialize, const N: usize>(
+    records: &[T],
+    header: &[&str; N],
+    rows: &[[String; N]],
+    format: ReportFormat,
+) -> Result<String> {
+    let mut output = String::new();
+    match format {
+        ReportFormat::Json => {
+            output = serde_json::to_string_pretty(records)?;
+            output.push('\n');
+        }
+        ReportFormat::Csv => {
+            writeln!(output, "{}", header.join(","))?;
+            for row in rows {
+                writeln!(output, "{}", row.join(","))?;
+            }
+        }
+        ReportFormat::Table => {
+            // Right-align every column to its widest cell
+            let mut widths: [usize; N] = header.map(str::len);
+            for row in rows {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell.len());
+                }
+            }
+            let format_line = |cells: &[&str]| -> String {
+                cells.iter()
+                    .zip(widths)
+                    .map(|(cell, width)| format!("{:>width$}", cell, width = width))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+            };
+            writeln!(output, "{}", format_line(header))?;
+            for row in rows {
+                let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+                writeln!(output, "{}", format_line(&cells))?;
+            }
+        }
+    }
+    Ok(output)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_id_contributions_sum_to_similarity_score() {
+        let left = [1, 2, 3, 3, 3, 4];
+        let right = [3, 3, 3, 4, 5, 9];
+        let mut ids = id_contributions(&left, &right);
+        assert_eq!(ids.iter().map(|id| id.contribution).sum::<i64>(), 31);
+        assert_eq!(ids.len(), 6);
+
+        sort_id_contributions(&mut ids, SortKey::Contribution);
+        assert_eq!(ids[0], IdContribution { id: 3, left_count: 3, right_count: 3, contribution: 27 });
+    }
+
+    #[test]
+    fn test_format_pair_contributions_csv() {
+        let pairs = pair_contributions(&[3, 1], &[2, 7], PairingPolicy::Error).unwrap();
+        let csv = format_pair_contributions(&pairs, ReportFormat::Csv).unwrap();
+        assert_eq!(csv, "left,right,distance\n1,2,1\n3,7,4\n");
+    }
+
+    #[test]
+    fn test_pair_contributions_beyond_i32() {
+        let pairs = pair_contributions(&[-2000000000], &[2000000000], PairingPolicy::Error).unwrap();
+        assert_eq!(pairs[0].distance, 4000000000);
+    }
+}
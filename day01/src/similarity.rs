@@ -0,0 +1,103 @@
+use clap::ValueEnum;
+use counter::Counter;
+
+// Strategy to compute the similarity score between the location lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SimilarityStrategy {
+    // Count the ids of both lists in hash maps
+    Hash,
+    // Walk both sorted lists in a single pass, without hashing or allocation
+    SortMerge,
+}
+
+impl SimilarityStrategy {
+    // Compute the similarity score between two lists of location ids.
+    // The sort-merge strategy expects both lists to be sorted.
+    pub fn compute(&self, left_locations: &[i32], right_locations: &[i32]) -> i64 {
+        match self {
+            SimilarityStrategy::Hash => compute_similarity_score(left_locations, right_locations),
+            SimilarityStrategy::SortMerge => compute_similarity_score_sorted(left_locations, right_locations),
+        }
+    }
+}
+
+// Compute the similarity score between two sets of location ids
+pub fn compute_similarity_score(left_locations: &[i32], right_locations: &[i32]) -> i64 {
+    let left_counter: Counter<_> = left_locations.iter().collect();
+    let right_counter: Counter<_> = right_locations.iter().collect();
+    _compute_similarity_score_from_counters(left_counter, right_counter)
+}
+
+// Compute the similarity score between two sets of location ids from their counters
+fn _compute_similarity_score_from_counters(left_counter: Counter<&i32>, right_counter: Counter<&i32>) -> i64 {
+    let mut score: i64 = 0;
+    for (id, left_count) in left_counter.into_iter() {
+        if let Some(&right_count) = right_counter.get(id) {
+            score += *id as i64 * (left_count * right_count) as i64;
+        }
+    }
+    score
+}
+
+// Compute the similarity score between two sorted lists of location ids by merging them.
+// Each run of equal ids contributes the id times the length of the run in both lists.
+pub fn compute_similarity_score_sorted(left_locations: &[i32], right_locations: &[i32]) -> i64 {
+    let mut score: i64 = 0;
+    let (mut i, mut j) = (0, 0);
+
+    while i < left_locations.len() && j < right_locations.len() {
+        let (left_id, right_id) = (left_locations[i], right_locations[j]);
+        if left_id < right_id {
+            i += 1;
+        } else if left_id > right_id {
+            j += 1;
+        } else {
+            let left_count = run_length(&left_locations[i..]);
+            let right_count = run_length(&right_locations[j..]);
+            score += left_id as i64 * (left_count * right_count) as i64;
+            i += left_count;
+            j += right_count;
+        }
+    }
+
+    score
+}
+
+// Count how often the first id is repeated at the start of a sorted list
+fn run_length(locations: &[i32]) -> usize {
+    locations.iter().take_while(|&&id| id == locations[0]).count()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(SimilarityStrategy::Hash)]
+    #[test_case(SimilarityStrategy::SortMerge)]
+    fn test_similarity_score(strategy: SimilarityStrategy) {
+        let left = [1, 2, 3, 3, 3, 4];
+        let right = [3, 3, 3, 4, 5, 9];
+        assert_eq!(strategy.compute(&left, &right), 31);
+    }
+
+    #[test]
+    fn test_similarity_strategies_agree() {
+        let left = [-2, 0, 0, 1, 5, 5, 5, 8];
+        let right = [-2, -2, 0, 5, 5, 7, 8, 8, 8];
+        assert_eq!(
+            SimilarityStrategy::Hash.compute(&left, &right),
+            SimilarityStrategy::SortMerge.compute(&left, &right),
+        );
+    }
+
+    // Scores beyond the range of an i32, like those of the larger bench lists
+    #[test_case(SimilarityStrategy::Hash)]
+    #[test_case(SimilarityStrategy::SortMerge)]
+    fn test_similarity_score_large(strategy: SimilarityStrategy) {
+        let left = [90_000; 200];
+        let right = [90_000; 300];
+        assert_eq!(strategy.compute(&left, &right), 90_000 * 200 * 300);
+    }
+}
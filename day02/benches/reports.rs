@@ -0,0 +1,39 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use day02::dampener::ProblemDampener;
+use day02::generator::{generate_reports, write_reports, GeneratorConfig};
+use day02::policy::SafetyPolicy;
+use day02::report::{count_safe_reports, read_reports};
+
+const N_REPORTS: usize = 2_000_000;
+
+// Generate the reports in memory on every run, so they always match the current generator
+fn synthetic_reports() -> Vec<u8> {
+    let config = GeneratorConfig { n_reports: N_REPORTS, ..GeneratorConfig::default() };
+    let generated = generate_reports(&config, &SafetyPolicy::default(), ProblemDampener::default())
+        .expect("Could not generate reports");
+    let mut contents: Vec<u8> = Vec::new();
+    write_reports(&generated.reports, &mut contents).expect("Could not write reports");
+    contents
+}
+
+fn bench_count_safe_reports(c: &mut Criterion) {
+    let contents = synthetic_reports();
+    let policy = SafetyPolicy::default();
+    let dampener = Some(ProblemDampener::default());
+
+    let mut group = c.benchmark_group("count_safe_reports");
+    group.sample_size(10);
+    group.bench_function("sequential", |b| {
+        b.iter(|| count_safe_reports(read_reports(contents.as_slice(), dampener).unwrap(), &policy))
+    });
+    #[cfg(feature = "parallel")]
+    group.bench_function("parallel", |b| {
+        use day02::report::{count_safe_reports_parallel, read_reports_parallel};
+        b.iter(|| count_safe_reports_parallel(read_reports_parallel(contents.as_slice(), dampener).unwrap(), &policy))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_count_safe_reports);
+criterion_main!(benches);
@@ -0,0 +1,163 @@
+use crate::policy::SafetyPolicy;
+
+
+// Dampens problems in a report by removing up to max_removals levels
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub struct ProblemDampener {
+    pub max_removals: usize,
+}
+
+// The puzzle's Problem Dampener tolerates a single bad level
+impl Default for ProblemDampener {
+    fn default() -> Self {
+        ProblemDampener { max_removals: 1 }
+    }
+}
+
+
+impl ProblemDampener {
+    // Whether the levels can be made safe by removing at most max_removals levels
+    pub fn dampen(&self, levels: &[i32], policy: &SafetyPolicy) -> bool {
+        self.find_removals(levels, policy).is_some()
+    }
+
+    // Find a minimal set of indices of levels to remove to make the levels safe,
+    // or None if that takes more than max_removals levels
+    pub fn find_removals(&self, levels: &[i32], policy: &SafetyPolicy) -> Option<Vec<usize>> {
+        match self.max_removals {
+            0 => policy.is_safe(levels).then(Vec::new),
+            1 => find_single_removal(levels, policy).map(|removal| removal.into_iter().collect()),
+            max_removals => Some(find_removals_longest_subsequence(levels, policy))
+                .filter(|removals| removals.len() <= max_removals),
+        }
+    }
+
+    // Dampen any problems in the levels by removing one level at a time and checking if the levels are safe
+    pub fn dampen_brute_force(&self, levels: &[i32], policy: &SafetyPolicy) -> bool {
+        let mut levels: Vec<i32> = levels.to_vec();
+        let mut safe: bool = policy.is_safe(&levels);
+        for i in 0..levels.len() {
+            if safe {
+                break;
+            }
+            let level = levels.remove(i);
+            safe = policy.is_safe(&levels);
+            levels.insert(i, level);
+        }
+        safe
+    }
+}
+
+
+// Find the level to remove to make the levels safe in a single pass over the levels.
+// Returns Some(None) if the levels are already safe and None if removing one level is not enough.
+// The first problem in the levels is either a level out of bounds, which has to be removed,
+// or a step which is not allowed, where one of the two levels of the step has to be removed.
+// So at most two candidates need to be checked.
+fn find_single_removal(levels: &[i32], policy: &SafetyPolicy) -> Option<Option<usize>> {
+    if policy.is_safe(levels) {
+        return Some(None);
+    }
+    policy.directions().iter().find_map(|&direction| {
+        let first_problem = (0..levels.len()).find(|&i| {
+            !policy.level_allowed(levels[i])
+                || (i + 1 < levels.len() && !policy.step_allowed(direction, levels[i], levels[i + 1]))
+        });
+        let Some(i) = first_problem else {
+            return Some(None);
+        };
+        let last_candidate = if policy.level_allowed(levels[i]) { i + 1 } else { i };
+        (i..=last_candidate)
+            .find(|&skip| policy.is_safe_skipping(levels, direction, Some(skip)))
+            .map(Some)
+    })
+}
+
+// Find the fewest levels to remove to make the levels safe, by finding the longest subsequence of the
+// levels which is safe. For each level, length[i] is the length of the longest safe subsequence ending
+// at that level, which extends the longest one ending at an earlier level which can step to it.
+fn find_removals_longest_subsequence(levels: &[i32], policy: &SafetyPolicy) -> Vec<usize> {
+    let mut best_kept: Vec<usize> = Vec::new();
+
+    for &direction in policy.directions() {
+        let mut length: Vec<usize> = vec![0; levels.len()];
+        let mut previous: Vec<Option<usize>> = vec![None; levels.len()];
+        for i in 0..levels.len() {
+            if !policy.level_allowed(levels[i]) {
+                continue;
+            }
+            length[i] = 1;
+            for j in 0..i {
+                if length[j] + 1 > length[i] && policy.step_allowed(direction, levels[j], levels[i]) {
+                    length[i] = length[j] + 1;
+                    previous[i] = Some(j);
+                }
+            }
+        }
+
+        let last = (0..levels.len()).max_by_key(|&i| length[i]);
+        if let Some(last) = last.filter(|&last| length[last] > best_kept.len()) {
+            best_kept = std::iter::successors(Some(last), |&i| previous[i]).collect();
+        }
+    }
+
+    (0..levels.len()).filter(|i| !best_kept.contains(i)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::tests::policies;
+    use proptest::prelude::*;
+    use test_case::test_case;
+
+    #[test_case(vec![7, 6, 4, 2, 1], true)]
+    #[test_case(vec![1, 2, 7, 8, 9], false)]
+    #[test_case(vec![9, 7, 6, 2, 1], false)]
+    #[test_case(vec![1, 3, 2, 4, 5], true)]
+    #[test_case(vec![8, 6, 4, 4, 1], true)]
+    #[test_case(vec![1, 3, 6, 7, 9], true)]
+    #[test_case(vec![10, 1, 2, 3], true; "first level removed")]
+    #[test_case(vec![1, 2, 3, 10], true; "last level removed")]
+    fn test_dampen(levels: Vec<i32>, expected: bool) {
+        assert_eq!(ProblemDampener::default().dampen(&levels, &SafetyPolicy::default()), expected);
+    }
+
+    #[test_case(vec![1, 2, 9, 8, 3, 4], 2, Some(vec![2, 3]))]
+    #[test_case(vec![1, 2, 9, 8, 3, 4], 1, None)]
+    #[test_case(vec![5, 1, 2, 3], 3, Some(vec![0]))]
+    #[test_case(vec![1, 2, 3], 3, Some(vec![]))]
+    fn test_find_removals(levels: Vec<i32>, max_removals: usize, expected: Option<Vec<usize>>) {
+        let dampener = ProblemDampener { max_removals };
+        assert_eq!(dampener.find_removals(&levels, &SafetyPolicy::default()), expected);
+    }
+
+    proptest! {
+        #[test]
+        fn test_dampen_matches_brute_force(
+            levels in proptest::collection::vec(0..10, 0..9),
+            policy in policies(),
+        ) {
+            let dampener = ProblemDampener::default();
+            prop_assert_eq!(dampener.dampen(&levels, &policy), dampener.dampen_brute_force(&levels, &policy));
+        }
+
+        #[test]
+        fn test_longest_subsequence_removals_are_minimal(
+            levels in proptest::collection::vec(0..10, 0..9),
+            policy in policies(),
+        ) {
+            let removals = find_removals_longest_subsequence(&levels, &policy);
+            let kept: Vec<i32> = (0..levels.len()).filter(|i| !removals.contains(i)).map(|i| levels[i]).collect();
+            prop_assert!(policy.is_safe(&kept));
+
+            // Agrees with the single pass dampener on whether one removal is enough
+            let single_removal = find_single_removal(&levels, &policy);
+            prop_assert_eq!(removals.len() <= 1, single_removal.is_some());
+            if let Some(removal) = single_removal {
+                prop_assert_eq!(removals.len(), removal.into_iter().count());
+            }
+        }
+    }
+}
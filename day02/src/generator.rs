@@ -0,0 +1,211 @@
+use std::io::{self, Write};
+use anyhow::{bail, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::dampener::ProblemDampener;
+use crate::policy::{Direction, SafetyPolicy};
+use crate::report::{evaluate_levels, SafetyVerdict};
+use crate::stream::ReportCounts;
+
+// Levels are drawn from this range when the policy does not bound them
+const DEFAULT_MIN_LEVEL: i32 = 1;
+const DEFAULT_MAX_LEVEL: i32 = 99;
+
+// Give up on a report after this many rejected candidates
+const MAX_ATTEMPTS: usize = 10_000;
+
+// Settings for generating synthetic reports
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorConfig {
+    pub n_reports: usize,
+    // Fraction of reports which are safe without dampening
+    pub safe_fraction: f64,
+    // Fraction of reports which are only safe after dampening, the rest is unsafe
+    pub dampenable_fraction: f64,
+    pub min_levels: usize,
+    pub max_levels: usize,
+    pub seed: u64,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        GeneratorConfig {
+            n_reports: 1000,
+            safe_fraction: 0.4,
+            dampenable_fraction: 0.2,
+            min_levels: 5,
+            max_levels: 8,
+            seed: 0,
+        }
+    }
+}
+
+// Synthetic reports with the counts they are expected to evaluate to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedReports {
+    pub reports: Vec<Vec<i32>>,
+    pub expected: ReportCounts,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Safe,
+    Dampenable,
+    Unsafe,
+}
+
+// Generate seeded random reports with the configured mix of safe, dampenable and unsafe reports.
+// Each candidate report is evaluated against the policy and dampener, and rejected if it is not of the intended
+// kind, so the expected counts are exact.
+pub fn generate_reports(
+    config: &GeneratorConfig,
+    policy: &SafetyPolicy,
+    problem_dampener: ProblemDampener,
+) -> Result<GeneratedReports> {
+    if config.min_levels < 2 || config.min_levels > config.max_levels {
+        bail!("invalid number of levels {}..={}", config.min_levels, config.max_levels);
+    }
+    if config.safe_fraction < 0.0 || config.dampenable_fraction < 0.0
+        || config.safe_fraction + config.dampenable_fraction > 1.0 {
+        bail!("invalid fractions {} safe and {} dampenable", config.safe_fraction, config.dampenable_fraction);
+    }
+
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut reports: Vec<Vec<i32>> = Vec::with_capacity(config.n_reports);
+    let mut expected = ReportCounts::default();
+
+    for _ in 0..config.n_reports {
+        let draw: f64 = rng.gen();
+        let kind = if draw < config.safe_fraction {
+            Kind::Safe
+        } else if draw < config.safe_fraction + config.dampenable_fraction {
+            Kind::Dampenable
+        } else {
+            Kind::Unsafe
+        };
+        let levels = generate_report(&mut rng, kind, config, policy, problem_dampener)?;
+        expected.add(&evaluate_levels(&levels, policy, Some(problem_dampener)));
+        reports.push(levels);
+    }
+
+    Ok(GeneratedReports { reports, expected })
+}
+
+// Write the reports with the levels of one report on each line
+pub fn write_reports<W: Write>(reports: &[Vec<i32>], mut writer: W) -> io::Result<()> {
+    for levels in reports {
+        let levels: Vec<String> = levels.iter().map(i32::to_string).collect();
+        writeln!(writer, "{}", levels.join(" "))?;
+    }
+    writer.flush()
+}
+
+fn generate_report(
+    rng: &mut StdRng,
+    kind: Kind,
+    config: &GeneratorConfig,
+    policy: &SafetyPolicy,
+    problem_dampener: ProblemDampener,
+) -> Result<Vec<i32>> {
+    for _ in 0..MAX_ATTEMPTS {
+        let n_levels = rng.gen_range(config.min_levels..=config.max_levels);
+        // Start from safe levels and insert random levels to break them
+        let n_inserted = match kind {
+            Kind::Safe => 0,
+            Kind::Dampenable => 1.min(n_levels - 1),
+            Kind::Unsafe => (problem_dampener.max_removals + 1).min(n_levels - 1),
+        };
+        let mut levels = generate_safe_levels(rng, n_levels - n_inserted, policy);
+        for _ in 0..n_inserted {
+            let index = rng.gen_range(0..=levels.len());
+            levels.insert(index, random_level(rng, policy));
+        }
+
+        let generated_kind = match evaluate_levels(&levels, policy, Some(problem_dampener)) {
+            SafetyVerdict::Safe => Kind::Safe,
+            SafetyVerdict::SafeAfterRemoving(_) => Kind::Dampenable,
+            SafetyVerdict::Unsafe(_) => Kind::Unsafe,
+        };
+        if generated_kind == kind {
+            return Ok(levels);
+        }
+    }
+    bail!("could not generate a {:?} report within {} attempts", kind, MAX_ATTEMPTS)
+}
+
+// Generate levels which follow the policy, unless the level bounds get in the way
+fn generate_safe_levels(rng: &mut StdRng, n_levels: usize, policy: &SafetyPolicy) -> Vec<i32> {
+    let directions = policy.directions();
+    let direction = directions[rng.gen_range(0..directions.len())];
+    let mut level = random_level(rng, policy);
+    let mut levels = vec![level];
+    while levels.len() < n_levels {
+        let step = rng.gen_range(policy.min_step..=policy.max_step);
+        let upwards = match direction {
+            Direction::Increasing => true,
+            Direction::Decreasing => false,
+            Direction::Either | Direction::Any => rng.gen(),
+        };
+        level = if upwards { level + step } else { level - step };
+        levels.push(level);
+    }
+    levels
+}
+
+// Draw a level within the policy bounds, where a missing bound defaults so the range is never empty
+fn random_level(rng: &mut StdRng, policy: &SafetyPolicy) -> i32 {
+    let min_level = policy.min_level.unwrap_or(policy.max_level.map_or(DEFAULT_MIN_LEVEL, |max_level| max_level.min(DEFAULT_MIN_LEVEL)));
+    let max_level = policy.max_level.unwrap_or(DEFAULT_MAX_LEVEL.max(min_level));
+    rng.gen_range(min_level..=max_level)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::count_safe_reports_streaming;
+    use test_case::test_case;
+
+    #[test]
+    fn test_generated_reports_match_expected_counts() {
+        let config = GeneratorConfig { n_reports: 500, seed: 7, ..GeneratorConfig::default() };
+        let policy = SafetyPolicy::default();
+        let dampener = ProblemDampener::default();
+        let generated = generate_reports(&config, &policy, dampener).unwrap();
+
+        let mut output: Vec<u8> = Vec::new();
+        write_reports(&generated.reports, &mut output).unwrap();
+        let counts = count_safe_reports_streaming(output.as_slice(), &policy, Some(dampener)).unwrap();
+        assert_eq!(counts, generated.expected);
+        assert_eq!(counts.n_reports, 500);
+        assert!(counts.n_dampened > 0 && counts.n_unsafe() > 0);
+    }
+
+    #[test]
+    fn test_generate_reports_is_seeded() {
+        let config = GeneratorConfig { n_reports: 50, seed: 3, ..GeneratorConfig::default() };
+        let generate = || generate_reports(&config, &SafetyPolicy::default(), ProblemDampener::default()).unwrap();
+        assert_eq!(generate(), generate());
+    }
+
+    #[test]
+    fn test_generate_reports_with_k_removals() {
+        let config = GeneratorConfig { n_reports: 200, min_levels: 6, max_levels: 10, ..GeneratorConfig::default() };
+        let policy = SafetyPolicy::default();
+        let dampener = ProblemDampener { max_removals: 2 };
+        let generated = generate_reports(&config, &policy, dampener).unwrap();
+        assert!(generated.reports.iter().all(|levels| levels.len() >= 6 && levels.len() <= 10));
+        assert!(generated.expected.n_dampened > 0 && generated.expected.n_unsafe() > 0);
+    }
+
+    #[test_case(None, Some(0))]
+    #[test_case(None, Some(-50))]
+    #[test_case(Some(150), None)]
+    fn test_generate_reports_with_one_level_bound(min_level: Option<i32>, max_level: Option<i32>) {
+        let config = GeneratorConfig { n_reports: 100, ..GeneratorConfig::default() };
+        let policy = SafetyPolicy { min_level, max_level, ..SafetyPolicy::default() };
+        let generated = generate_reports(&config, &policy, ProblemDampener::default()).unwrap();
+        assert_eq!(generated.expected.n_reports, 100);
+    }
+}
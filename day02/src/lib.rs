@@ -0,0 +1,5 @@
+pub mod dampener;
+pub mod generator;
+pub mod policy;
+pub mod report;
+pub mod stream;
@@ -1,119 +1,221 @@
 use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::cmp::Ordering;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use clap::Parser;
+
+use day02::dampener::ProblemDampener;
+use day02::generator::{generate_reports, write_reports, GeneratorConfig};
+use day02::policy::{Direction, SafetyPolicy};
+use day02::report::{count_safe_reports, read_reports, Report};
+use day02::stream::{evaluate_reports, ReportCounts};
+
+#[derive(Parser, Debug)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Reports with the levels of one report on each line, or - to read from stdin
+    #[arg(long, default_value = "reports.txt")]
+    input: PathBuf,
+
+    #[command(flatten)]
+    policy: PolicyArgs,
+
+    /// Maximum number of levels the Problem Dampener may remove from a report
+    #[arg(long, default_value_t = 1, global = true)]
+    max_removals: usize,
+
+    /// Print the verdict for each report
+    #[arg(long)]
+    verdicts: bool,
+
+    /// Print the verdict and running counts as each report is read
+    #[arg(long, conflicts_with = "verdicts")]
+    stream: bool,
+
+    /// Read all reports into memory and evaluate them in parallel, needs the parallel feature
+    #[arg(long, conflicts_with_all = ["verdicts", "stream"])]
+    parallel: bool,
+}
 
-fn main() {
-    let reports: Vec<Report> = read_reports();
-    let n_safe_reports: usize = count_safe_reports(reports);
-    println!("Number of safe reports: {}", n_safe_reports);
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Generate seeded random reports and print the expected counts to stderr
+    Generate(GenerateArgs),
 }
 
+#[derive(clap::Args, Debug)]
+struct GenerateArgs {
+    /// Number of reports to generate
+    #[arg(long, default_value_t = 1000)]
+    n_reports: usize,
 
-#[derive(Clone)]
-#[derive(Debug)]
-struct ProblemDampener {}
+    /// Fraction of reports which are safe without dampening
+    #[arg(long, default_value_t = 0.4)]
+    safe_fraction: f64,
 
-#[derive(Debug)]
-struct Report {
-    levels: Vec<i32>,
-    problem_dampener: Option<ProblemDampener>,
-}
+    /// Fraction of reports which are only safe after dampening
+    #[arg(long, default_value_t = 0.2)]
+    dampenable_fraction: f64,
 
+    /// Minimum number of levels in a report
+    #[arg(long, default_value_t = 5)]
+    min_levels: usize,
 
-// Levels are strictly monotonic if they are either strictly increasing or strictly decreasing
-fn are_strictly_monotonic(levels: &Vec<i32>) -> bool {
-    let mut increasing: bool = true;
-    let mut decreasing: bool = true;
+    /// Maximum number of levels in a report
+    #[arg(long, default_value_t = 8)]
+    max_levels: usize,
 
-    for window in levels.windows(2) {
-        match window[0].cmp(&window[1]) {
-            Ordering::Less => decreasing = false,
-            Ordering::Greater => increasing = false,
-            Ordering::Equal => {decreasing = false; increasing = false;}
-        }
-        if !increasing && !decreasing {
-            break;
-        }
-    }
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
 
-    increasing || decreasing
+    /// File to write the reports to, defaults to stdout
+    #[arg(long)]
+    output: Option<PathBuf>,
 }
 
+// Command line options to load and override the safety policy
+#[derive(clap::Args, Debug)]
+struct PolicyArgs {
+    /// TOML file with the safety policy, missing fields take the puzzle rules
+    #[arg(long = "policy", global = true)]
+    policy_file: Option<PathBuf>,
 
-// The difference between each level must be within the bounds of lower and upper
-fn difference_within_bounds(levels: &Vec<i32>, lower: i32, upper: i32) -> bool {
-    let mut differences: Vec<i32> = Vec::new();
+    /// Direction in which the levels must move
+    #[arg(long, value_enum, global = true)]
+    direction: Option<Direction>,
 
-    for window in levels.windows(2) {
-        differences.push((window[1] - window[0]).abs());
-    }
+    /// Minimum absolute difference between adjacent levels
+    #[arg(long, global = true)]
+    min_step: Option<i32>,
 
-    differences.iter().all(|&x| x >= lower && x <= upper)
-}
+    /// Maximum absolute difference between adjacent levels
+    #[arg(long, global = true)]
+    max_step: Option<i32>,
 
+    /// Minimum allowed level
+    #[arg(long, allow_hyphen_values = true, global = true)]
+    min_level: Option<i32>,
 
-// Levels are considered safe if they are strictly monotonic and the difference between each level is within 1 and 3
-fn safe_levels(levels: &Vec<i32>) -> bool {
-    are_strictly_monotonic(levels) && difference_within_bounds(levels, 1, 3)
+    /// Maximum allowed level
+    #[arg(long, allow_hyphen_values = true, global = true)]
+    max_level: Option<i32>,
 }
 
-
-impl ProblemDampener {
-    // Dampen any problems in the levels by removing one level at a time and checking if the levels are safe
-    fn dampen(&self, levels: &mut Vec<i32>) -> bool {
-        let mut safe: bool = true;
-        for i in 0..levels.len() {
-            let level = levels[i];
-            levels.remove(i);
-            safe = safe_levels(&levels);
-            if safe {
-                break;
-            }
-            levels.insert(i, level);
-        }
-        safe
+impl PolicyArgs {
+    // Load the policy file, or the puzzle rules, and apply the overrides from the command line
+    fn to_policy(&self) -> Result<SafetyPolicy> {
+        let mut policy = match &self.policy_file {
+            Some(path) => SafetyPolicy::from_file(path)?,
+            None => SafetyPolicy::default(),
+        };
+        policy.direction = self.direction.unwrap_or(policy.direction);
+        policy.min_step = self.min_step.unwrap_or(policy.min_step);
+        policy.max_step = self.max_step.unwrap_or(policy.max_step);
+        policy.min_level = self.min_level.or(policy.min_level);
+        policy.max_level = self.max_level.or(policy.max_level);
+        policy.validate()?;
+        Ok(policy)
     }
 }
 
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let policy = args.policy.to_policy()?;
+    let problem_dampener = Some(ProblemDampener { max_removals: args.max_removals });
+    if let Some(Command::Generate(generate_args)) = &args.command {
+        return generate(generate_args, &policy, ProblemDampener { max_removals: args.max_removals });
+    }
+    let reader = open_input(&args.input)?;
+
+    let n_safe_reports: usize = if args.stream {
+        stream_verdicts(reader, &policy, problem_dampener)?
+    } else if args.verdicts {
+        let reports: Vec<Report> = read_reports(reader, problem_dampener)?;
+        print_verdicts(&reports, &policy);
+        count_safe_reports(reports, &policy)
+    } else if args.parallel {
+        count_safe_parallel(reader, &policy, problem_dampener)?
+    } else {
+        day02::stream::count_safe_reports_streaming(reader, &policy, problem_dampener)?.n_safe
+    };
+    println!("Number of safe reports: {}", n_safe_reports);
+    Ok(())
+}
 
-impl Report {
-    // A report is safe if it is monotonic and the difference between each level is within 1 and 3
-    fn is_safe(&self) -> bool {
-        let mut safe: bool = safe_levels(&self.levels);
-        let mut levels: Vec<i32> = self.levels.clone();
-
-        if !safe {
-            match &self.problem_dampener {
-                Some(dampener) => {safe = dampener.dampen(&mut levels)},
-                None => {}
-            }
+// Generate reports and write them to the output, printing the expected counts
+fn generate(args: &GenerateArgs, policy: &SafetyPolicy, problem_dampener: ProblemDampener) -> Result<()> {
+    let config = GeneratorConfig {
+        n_reports: args.n_reports,
+        safe_fraction: args.safe_fraction,
+        dampenable_fraction: args.dampenable_fraction,
+        min_levels: args.min_levels,
+        max_levels: args.max_levels,
+        seed: args.seed,
+    };
+    let generated = generate_reports(&config, policy, problem_dampener)?;
+    match &args.output {
+        Some(path) => {
+            let file = File::create(path).with_context(|| format!("could not create {}", path.display()))?;
+            write_reports(&generated.reports, io::BufWriter::new(file))?;
         }
+        None => write_reports(&generated.reports, io::stdout().lock())?,
+    }
+    eprintln!(
+        "Expected: {} safe reports ({} after dampening), {} unsafe reports",
+        generated.expected.n_safe, generated.expected.n_dampened, generated.expected.n_unsafe(),
+    );
+    Ok(())
+}
 
-        safe
+// Open the input file, where - is stdin
+fn open_input(path: &Path) -> Result<Box<dyn BufRead>> {
+    if path == Path::new("-") {
+        return Ok(Box::new(io::stdin().lock()));
     }
+    let file = File::open(path).with_context(|| format!("could not open {}", path.display()))?;
+    Ok(Box::new(BufReader::new(file)))
 }
 
+// Count the safe reports, parsing and evaluating them in parallel
+#[cfg(feature = "parallel")]
+fn count_safe_parallel<R: BufRead>(reader: R, policy: &SafetyPolicy, problem_dampener: Option<ProblemDampener>) -> Result<usize> {
+    use day02::report::{count_safe_reports_parallel, read_reports_parallel};
+    Ok(count_safe_reports_parallel(read_reports_parallel(reader, problem_dampener)?, policy))
+}
 
-// Get the number of safe reports
-fn count_safe_reports(reports: Vec<Report>) -> usize {
-    reports.into_iter().filter(|r: &Report| r.is_safe()).count()
+#[cfg(not(feature = "parallel"))]
+fn count_safe_parallel<R: BufRead>(_reader: R, _policy: &SafetyPolicy, _problem_dampener: Option<ProblemDampener>) -> Result<usize> {
+    anyhow::bail!("--parallel needs day02 to be built with the parallel feature")
 }
 
-// Read reports from the reports.txt file
-fn read_reports() -> Vec<Report> {
-    let mut reports: Vec<Report> = Vec::new();
-    let file: File = File::open("reports.txt").expect("Could not open file");
-    let reader: BufReader<File> = BufReader::new(file);
-    let problem_dampener: Option<ProblemDampener> = Some(ProblemDampener {});
-    // let problem_dampener: Option<ProblemDampener> = None;
-    for line in reader.lines() {
-        let line: String = line.expect("Could not read line");
-        let levels: Vec<i32> = line
-            .split_whitespace()
-            .map(|s: &str| s.parse().expect("Could not parse number"))
-            .collect();
-        reports.push(Report { levels, problem_dampener: problem_dampener.clone() });
+// Print the verdict for each report as it is read, with the running counts
+fn stream_verdicts<R: BufRead>(reader: R, policy: &SafetyPolicy, problem_dampener: Option<ProblemDampener>) -> Result<usize> {
+    let mut counts = ReportCounts::default();
+    for evaluation in evaluate_reports(reader, policy, problem_dampener) {
+        let evaluation = evaluation?;
+        counts = evaluation.counts;
+        println!(
+            "{:>5}  {}  ({} safe, {} unsafe)",
+            evaluation.line,
+            evaluation.verdict.describe(&evaluation.levels),
+            counts.n_safe,
+            counts.n_unsafe(),
+        );
     }
+    Ok(counts.n_safe)
+}
 
-    reports
+// Print a table with the verdict for each report, by line number
+fn print_verdicts(reports: &[Report], policy: &SafetyPolicy) {
+    let descriptions: Vec<String> = reports.iter()
+        .map(|report| report.describe(&report.evaluate(policy)))
+        .collect();
+    let width = descriptions.iter().map(String::len).max().unwrap_or(0).max("verdict".len());
+    println!("{:>5}  {:<width$}  levels", "line", "verdict", width = width);
+    for (i, (report, description)) in reports.iter().zip(&descriptions).enumerate() {
+        let levels: Vec<String> = report.levels.iter().map(i32::to_string).collect();
+        println!("{:>5}  {:<width$}  {}", i + 1, description, levels.join(" "), width = width);
+    }
 }
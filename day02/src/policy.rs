@@ -0,0 +1,304 @@
+use std::fmt;
+use std::path::Path;
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+
+// Direction in which the levels of a report must move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    // Every level is higher than the previous one
+    Increasing,
+    // Every level is lower than the previous one
+    Decreasing,
+    // Levels are either all increasing or all decreasing
+    Either,
+    // Levels may move up and down
+    #[serde(alias = "none")]
+    #[value(alias = "none")]
+    Any,
+}
+
+// A rule of the safety policy broken by the levels of a report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    LevelOutOfBounds { level: i32 },
+    WrongDirection { from: i32, to: i32 },
+    StepTooSmall { from: i32, to: i32 },
+    StepTooLarge { from: i32, to: i32 },
+}
+
+// The first rule broken by the levels of a report, at the index of the level or of the first level of the step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Violation {
+    pub index: usize,
+    pub rule: Rule,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::LevelOutOfBounds { level } => write!(f, "level {} out of bounds", level),
+            Rule::WrongDirection { from, to } => write!(f, "step {} -> {} in the wrong direction", from, to),
+            Rule::StepTooSmall { from, to } => write!(f, "step {} -> {} too small", from, to),
+            Rule::StepTooLarge { from, to } => write!(f, "step {} -> {} too large", from, to),
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at index {}", self.rule, self.index)
+    }
+}
+
+// Rules which the levels of a report must follow to be considered safe
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SafetyPolicy {
+    pub direction: Direction,
+    // Bounds on the absolute difference between adjacent levels
+    pub min_step: i32,
+    pub max_step: i32,
+    // Optional bounds on the levels themselves
+    pub min_level: Option<i32>,
+    pub max_level: Option<i32>,
+}
+
+// The puzzle rules: levels are strictly monotonic and adjacent levels differ by 1 to 3
+impl Default for SafetyPolicy {
+    fn default() -> Self {
+        SafetyPolicy {
+            direction: Direction::Either,
+            min_step: 1,
+            max_step: 3,
+            min_level: None,
+            max_level: None,
+        }
+    }
+}
+
+impl SafetyPolicy {
+    // Read a safety policy from a TOML file, where missing fields take the puzzle rules
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("could not read safety policy {}", path.display()))?;
+        let policy: SafetyPolicy = toml::from_str(&contents)
+            .with_context(|| format!("could not parse safety policy {}", path.display()))?;
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    // Check that the bounds of the policy are consistent
+    pub fn validate(&self) -> Result<()> {
+        if self.min_step < 0 || self.min_step > self.max_step {
+            bail!("invalid step bounds {}..={}", self.min_step, self.max_step);
+        }
+        if let (Some(min_level), Some(max_level)) = (self.min_level, self.max_level) {
+            if min_level > max_level {
+                bail!("invalid level bounds {}..={}", min_level, max_level);
+            }
+        }
+        Ok(())
+    }
+
+    // Levels are safe if they are all within the level bounds and every step follows the direction and step bounds
+    pub fn is_safe(&self, levels: &[i32]) -> bool {
+        if !levels.iter().all(|&level| self.level_allowed(level)) {
+            return false;
+        }
+        let steps_allowed = |direction: Direction| {
+            levels.windows(2).all(|window| self.step_allowed(direction, window[0], window[1]))
+        };
+        match self.direction {
+            Direction::Either => steps_allowed(Direction::Increasing) || steps_allowed(Direction::Decreasing),
+            direction => steps_allowed(direction),
+        }
+    }
+
+    // Find the first rule broken by the levels, or None if the levels are safe.
+    // If the levels may move in either direction, the first step which moves decides the direction.
+    pub fn first_violation(&self, levels: &[i32]) -> Option<Violation> {
+        let direction = match self.direction {
+            Direction::Either => match levels.windows(2).find(|window| window[0] != window[1]) {
+                Some(window) if window[0] > window[1] => Direction::Decreasing,
+                _ => Direction::Increasing,
+            },
+            direction => direction,
+        };
+
+        for (index, &level) in levels.iter().enumerate() {
+            if !self.level_allowed(level) {
+                return Some(Violation { index, rule: Rule::LevelOutOfBounds { level } });
+            }
+            let Some(&next) = levels.get(index + 1) else {
+                break;
+            };
+            let step = step(direction, level, next);
+            let rule = if step < 0 {
+                Rule::WrongDirection { from: level, to: next }
+            } else if step < self.min_step as i64 {
+                Rule::StepTooSmall { from: level, to: next }
+            } else if step > self.max_step as i64 {
+                Rule::StepTooLarge { from: level, to: next }
+            } else {
+                continue;
+            };
+            return Some(Violation { index, rule });
+        }
+        None
+    }
+
+    // Whether the levels follow the policy when moving in the given direction, skipping the level at index skip.
+    // The direction must not be Either.
+    pub fn is_safe_skipping(&self, levels: &[i32], direction: Direction, skip: Option<usize>) -> bool {
+        let mut kept = levels.iter()
+            .enumerate()
+            .filter(|&(i, _)| Some(i) != skip)
+            .map(|(_, &level)| level);
+        let Some(mut previous) = kept.next() else {
+            return true;
+        };
+        if !self.level_allowed(previous) {
+            return false;
+        }
+        for level in kept {
+            if !self.level_allowed(level) || !self.step_allowed(direction, previous, level) {
+                return false;
+            }
+            previous = level;
+        }
+        true
+    }
+
+    // The directions to try for a report: both for Either, otherwise the direction itself
+    pub fn directions(&self) -> &'static [Direction] {
+        match self.direction {
+            Direction::Increasing => &[Direction::Increasing],
+            Direction::Decreasing => &[Direction::Decreasing],
+            Direction::Either => &[Direction::Increasing, Direction::Decreasing],
+            Direction::Any => &[Direction::Any],
+        }
+    }
+
+    // Whether a level is within the level bounds
+    pub fn level_allowed(&self, level: i32) -> bool {
+        self.min_level.is_none_or(|min_level| level >= min_level)
+            && self.max_level.is_none_or(|max_level| level <= max_level)
+    }
+
+    // Whether the step from one level to the next is allowed when moving in the given direction
+    pub fn step_allowed(&self, direction: Direction, from: i32, to: i32) -> bool {
+        let step = step(direction, from, to);
+        step >= self.min_step as i64 && step <= self.max_step as i64
+    }
+}
+
+// The step from one level to the next when moving in the given direction, negative if it moves the other way.
+// Levels are i32 and the step between them may not fit one, so it is an i64.
+fn step(direction: Direction, from: i32, to: i32) -> i64 {
+    let (from, to) = (from as i64, to as i64);
+    match direction {
+        Direction::Increasing => to - from,
+        Direction::Decreasing => from - to,
+        Direction::Either | Direction::Any => (to - from).abs(),
+    }
+}
+
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use test_case::test_case;
+
+    pub(crate) fn policies() -> impl Strategy<Value = SafetyPolicy> {
+        let direction = prop_oneof![
+            Just(Direction::Increasing),
+            Just(Direction::Decreasing),
+            Just(Direction::Either),
+            Just(Direction::Any),
+        ];
+        (direction, 0..3, 0..4, proptest::option::of(0..4), proptest::option::of(6..10)).prop_map(
+            |(direction, min_step, extra_step, min_level, max_level)| SafetyPolicy {
+                direction,
+                min_step,
+                max_step: min_step + extra_step,
+                min_level,
+                max_level,
+            },
+        )
+    }
+
+
+    #[test_case(vec![7, 6, 4, 2, 1], true)]
+    #[test_case(vec![1, 2, 7, 8, 9], false)]
+    #[test_case(vec![9, 7, 6, 2, 1], false)]
+    #[test_case(vec![1, 3, 2, 4, 5], false)]
+    #[test_case(vec![8, 6, 4, 4, 1], false)]
+    #[test_case(vec![1, 3, 6, 7, 9], true)]
+    fn test_default_policy(levels: Vec<i32>, expected: bool) {
+        assert_eq!(SafetyPolicy::default().is_safe(&levels), expected);
+    }
+
+    #[test_case(Direction::Increasing, vec![1, 3, 6], true)]
+    #[test_case(Direction::Increasing, vec![6, 3, 1], false)]
+    #[test_case(Direction::Decreasing, vec![6, 3, 1], true)]
+    #[test_case(Direction::Any, vec![1, 3, 1, 4], true)]
+    #[test_case(Direction::Either, vec![1, 3, 1, 4], false)]
+    fn test_direction(direction: Direction, levels: Vec<i32>, expected: bool) {
+        let policy = SafetyPolicy { direction, ..SafetyPolicy::default() };
+        assert_eq!(policy.is_safe(&levels), expected);
+    }
+
+    #[test_case(Direction::Increasing)]
+    #[test_case(Direction::Decreasing)]
+    #[test_case(Direction::Either)]
+    #[test_case(Direction::Any)]
+    fn test_step_between_extreme_levels(direction: Direction) {
+        let policy = SafetyPolicy { direction, ..SafetyPolicy::default() };
+        assert!(!policy.is_safe(&[i32::MIN, i32::MAX]));
+        assert!(!policy.is_safe(&[i32::MAX, i32::MIN]));
+    }
+
+    #[test]
+    fn test_level_bounds() {
+        let policy = SafetyPolicy { min_level: Some(0), max_level: Some(5), ..SafetyPolicy::default() };
+        assert!(policy.is_safe(&[0, 2, 5]));
+        assert!(!policy.is_safe(&[2, 5, 6]));
+    }
+
+    #[test_case(vec![1, 2, 7, 8, 9], Violation { index: 1, rule: Rule::StepTooLarge { from: 2, to: 7 } })]
+    #[test_case(vec![8, 6, 4, 4, 1], Violation { index: 2, rule: Rule::StepTooSmall { from: 4, to: 4 } })]
+    #[test_case(vec![1, 3, 2, 4, 5], Violation { index: 1, rule: Rule::WrongDirection { from: 3, to: 2 } })]
+    #[test_case(vec![i32::MIN, i32::MAX], Violation { index: 0, rule: Rule::StepTooLarge { from: i32::MIN, to: i32::MAX } })]
+    #[test_case(vec![i32::MAX, i32::MIN], Violation { index: 0, rule: Rule::StepTooLarge { from: i32::MAX, to: i32::MIN } })]
+    fn test_first_violation(levels: Vec<i32>, expected: Violation) {
+        assert_eq!(SafetyPolicy::default().first_violation(&levels), Some(expected));
+    }
+
+    #[test]
+    fn test_first_violation_level_out_of_bounds() {
+        let policy = SafetyPolicy { max_level: Some(5), ..SafetyPolicy::default() };
+        let expected = Violation { index: 2, rule: Rule::LevelOutOfBounds { level: 6 } };
+        assert_eq!(policy.first_violation(&[3, 4, 6]), Some(expected));
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        let policy: SafetyPolicy = toml::from_str("direction = \"none\"\nmax_step = 5\n").unwrap();
+        assert_eq!(policy, SafetyPolicy { direction: Direction::Any, max_step: 5, ..SafetyPolicy::default() });
+    }
+
+    proptest! {
+        #[test]
+        fn test_first_violation_matches_is_safe(
+            levels in proptest::collection::vec(0..10, 0..9),
+            policy in policies(),
+        ) {
+            prop_assert_eq!(policy.first_violation(&levels).is_none(), policy.is_safe(&levels));
+        }
+    }
+}
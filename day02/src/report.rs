@@ -0,0 +1,168 @@
+use std::io::BufRead;
+use std::num::ParseIntError;
+use anyhow::{Context, Result};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::dampener::ProblemDampener;
+use crate::policy::{SafetyPolicy, Violation};
+
+
+#[derive(Debug)]
+pub struct Report {
+    pub levels: Vec<i32>,
+    pub problem_dampener: Option<ProblemDampener>,
+}
+
+
+// Outcome of evaluating a report against the safety policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SafetyVerdict {
+    Safe,
+    // Safe after the Problem Dampener removed the levels at these indices
+    SafeAfterRemoving(Vec<usize>),
+    // Unsafe, with the first rule broken by the levels before dampening
+    Unsafe(Violation),
+}
+
+impl SafetyVerdict {
+    pub fn is_safe(&self) -> bool {
+        !matches!(self, SafetyVerdict::Unsafe(_))
+    }
+
+    // Describe the verdict for the levels it was given for
+    pub fn describe(&self, levels: &[i32]) -> String {
+        match self {
+            SafetyVerdict::Safe => "safe".to_string(),
+            SafetyVerdict::SafeAfterRemoving(removals) => {
+                let removed: Vec<String> = removals.iter()
+                    .map(|&i| format!("{} at index {}", levels[i], i))
+                    .collect();
+                format!("safe after removing {}", removed.join(", "))
+            }
+            SafetyVerdict::Unsafe(violation) => format!("unsafe, {}", violation),
+        }
+    }
+}
+
+
+impl Report {
+    // Evaluate the levels against the safety policy, dampening any problems
+    pub fn evaluate(&self, policy: &SafetyPolicy) -> SafetyVerdict {
+        evaluate_levels(&self.levels, policy, self.problem_dampener)
+    }
+
+    // A report is safe if its levels follow the safety policy, possibly after dampening
+    pub fn is_safe(&self, policy: &SafetyPolicy) -> bool {
+        let mut safe: bool = policy.is_safe(&self.levels);
+
+        if !safe {
+            if let Some(dampener) = &self.problem_dampener {
+                safe = dampener.dampen(&self.levels, policy);
+            }
+        }
+
+        safe
+    }
+
+    // Describe the verdict for the levels of this report
+    pub fn describe(&self, verdict: &SafetyVerdict) -> String {
+        verdict.describe(&self.levels)
+    }
+}
+
+
+// Evaluate levels against the safety policy, dampening any problems
+pub fn evaluate_levels(levels: &[i32], policy: &SafetyPolicy, problem_dampener: Option<ProblemDampener>) -> SafetyVerdict {
+    let Some(violation) = policy.first_violation(levels) else {
+        return SafetyVerdict::Safe;
+    };
+    match problem_dampener.and_then(|dampener| dampener.find_removals(levels, policy)) {
+        Some(removals) => SafetyVerdict::SafeAfterRemoving(removals),
+        None => SafetyVerdict::Unsafe(violation),
+    }
+}
+
+
+// Get the number of safe reports
+pub fn count_safe_reports(reports: Vec<Report>, policy: &SafetyPolicy) -> usize {
+    reports.into_iter().filter(|r: &Report| r.is_safe(policy)).count()
+}
+
+// Get the number of safe reports, evaluating the reports in parallel
+#[cfg(feature = "parallel")]
+pub fn count_safe_reports_parallel(reports: Vec<Report>, policy: &SafetyPolicy) -> usize {
+    reports.into_par_iter().filter(|r: &Report| r.is_safe(policy)).count()
+}
+
+// Read reports with the levels of one report on each line
+pub fn read_reports<R: BufRead>(reader: R, problem_dampener: Option<ProblemDampener>) -> Result<Vec<Report>> {
+    reader.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let line = line.with_context(|| format!("could not read line {}", i + 1))?;
+            parse_report(&line, i + 1, problem_dampener)
+        })
+        .collect()
+}
+
+// Read reports with the levels of one report on each line, parsing the lines in parallel.
+// This reads the whole input into memory first.
+#[cfg(feature = "parallel")]
+pub fn read_reports_parallel<R: BufRead>(mut reader: R, problem_dampener: Option<ProblemDampener>) -> Result<Vec<Report>> {
+    let mut contents: String = String::new();
+    reader.read_to_string(&mut contents).context("could not read reports")?;
+    let lines: Vec<&str> = contents.lines().collect();
+    lines.par_iter()
+        .enumerate()
+        .map(|(i, line)| parse_report(line, i + 1, problem_dampener))
+        .collect()
+}
+
+// Parse a report from a line with its levels
+fn parse_report(line: &str, line_number: usize, problem_dampener: Option<ProblemDampener>) -> Result<Report> {
+    let levels: Vec<i32> = parse_levels(line)
+        .with_context(|| format!("could not parse line {}: '{}'", line_number, line))?;
+    Ok(Report { levels, problem_dampener })
+}
+
+// Parse the levels of a report from a line
+pub fn parse_levels(line: &str) -> Result<Vec<i32>, ParseIntError> {
+    line.split_whitespace().map(str::parse).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::Rule;
+    use test_case::test_case;
+
+    #[test_case(vec![7, 6, 4, 2, 1], SafetyVerdict::Safe)]
+    #[test_case(vec![1, 3, 2, 4, 5], SafetyVerdict::SafeAfterRemoving(vec![1]))]
+    #[test_case(vec![8, 6, 4, 4, 1], SafetyVerdict::SafeAfterRemoving(vec![2]))]
+    #[test_case(
+        vec![1, 2, 7, 8, 9],
+        SafetyVerdict::Unsafe(Violation { index: 1, rule: Rule::StepTooLarge { from: 2, to: 7 } })
+    )]
+    fn test_evaluate(levels: Vec<i32>, expected: SafetyVerdict) {
+        let report = Report { levels, problem_dampener: Some(ProblemDampener::default()) };
+        assert_eq!(report.evaluate(&SafetyPolicy::default()), expected);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_count_matches_sequential() {
+        let policy = SafetyPolicy::default();
+        let open = || std::io::BufReader::new(std::fs::File::open("reports.txt").unwrap());
+        let reports = || read_reports(open(), Some(ProblemDampener::default())).unwrap();
+        let parallel_reports = read_reports_parallel(open(), Some(ProblemDampener::default())).unwrap();
+        assert_eq!(parallel_reports.len(), reports().len());
+        assert_eq!(count_safe_reports_parallel(parallel_reports, &policy), count_safe_reports(reports(), &policy));
+    }
+
+    #[test]
+    fn test_read_reports_parse_error() {
+        let error = read_reports("1 2 3\n4 x 6\n".as_bytes(), None).unwrap_err();
+        assert_eq!(error.to_string(), "could not parse line 2: '4 x 6'");
+    }
+}
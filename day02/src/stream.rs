@@ -0,0 +1,99 @@
+use std::io::BufRead;
+use anyhow::{Context, Result};
+
+use crate::dampener::ProblemDampener;
+use crate::policy::SafetyPolicy;
+use crate::report::{evaluate_levels, parse_levels, SafetyVerdict};
+
+
+// Running counts of the reports evaluated so far
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReportCounts {
+    pub n_reports: usize,
+    pub n_safe: usize,
+    // Safe reports which needed the Problem Dampener
+    pub n_dampened: usize,
+}
+
+impl ReportCounts {
+    pub fn add(&mut self, verdict: &SafetyVerdict) {
+        self.n_reports += 1;
+        match verdict {
+            SafetyVerdict::Safe => self.n_safe += 1,
+            SafetyVerdict::SafeAfterRemoving(_) => {
+                self.n_safe += 1;
+                self.n_dampened += 1;
+            }
+            SafetyVerdict::Unsafe(_) => {}
+        }
+    }
+
+    pub fn n_unsafe(&self) -> usize {
+        self.n_reports - self.n_safe
+    }
+}
+
+// A report evaluated by the streaming pipeline, with the running counts up to and including it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Evaluation {
+    pub line: usize,
+    pub levels: Vec<i32>,
+    pub verdict: SafetyVerdict,
+    pub counts: ReportCounts,
+}
+
+// Lazily evaluate the reports read line by line from the reader
+pub fn evaluate_reports<'a, R: BufRead + 'a>(
+    reader: R,
+    policy: &'a SafetyPolicy,
+    problem_dampener: Option<ProblemDampener>,
+) -> impl Iterator<Item = Result<Evaluation>> + 'a {
+    let mut counts = ReportCounts::default();
+    reader.lines().enumerate().map(move |(i, line)| {
+        let line = line.with_context(|| format!("could not read line {}", i + 1))?;
+        let levels = parse_levels(&line).with_context(|| format!("could not parse line {}: '{}'", i + 1, line))?;
+        let verdict = evaluate_levels(&levels, policy, problem_dampener);
+        counts.add(&verdict);
+        Ok(Evaluation { line: i + 1, levels, verdict, counts })
+    })
+}
+
+// Count the safe reports read line by line from the reader
+pub fn count_safe_reports_streaming<R: BufRead>(
+    reader: R,
+    policy: &SafetyPolicy,
+    problem_dampener: Option<ProblemDampener>,
+) -> Result<ReportCounts> {
+    evaluate_reports(reader, policy, problem_dampener)
+        .try_fold(ReportCounts::default(), |_, evaluation| evaluation.map(|evaluation| evaluation.counts))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "7 6 4 2 1\n1 2 7 8 9\n9 7 6 2 1\n1 3 2 4 5\n8 6 4 4 1\n1 3 6 7 9\n";
+
+    #[test]
+    fn test_count_safe_reports_streaming() {
+        let counts = count_safe_reports_streaming(EXAMPLE.as_bytes(), &SafetyPolicy::default(), Some(ProblemDampener::default()));
+        assert_eq!(counts.unwrap(), ReportCounts { n_reports: 6, n_safe: 4, n_dampened: 2 });
+    }
+
+    #[test]
+    fn test_evaluate_reports_running_counts() {
+        let n_safe: Vec<usize> = evaluate_reports(EXAMPLE.as_bytes(), &SafetyPolicy::default(), None)
+            .map(|evaluation| evaluation.unwrap().counts.n_safe)
+            .collect();
+        assert_eq!(n_safe, vec![1, 1, 1, 1, 1, 2]);
+    }
+
+    #[test]
+    fn test_evaluate_reports_parse_error() {
+        let policy = SafetyPolicy::default();
+        let mut evaluations = evaluate_reports("1 2 3\n1 x 3\n".as_bytes(), &policy, None);
+        assert!(evaluations.next().unwrap().is_ok());
+        assert!(evaluations.next().unwrap().is_err());
+    }
+}
@@ -0,0 +1,51 @@
+// Feeds arbitrary memory to the scanner and interpreter, checking that nothing panics
+// and that the scanner finds the same instructions as the regex it replaced.
+// Run with: cargo +nightly fuzz run scanner
+#![no_main]
+
+use std::io;
+use std::ops::Range;
+use std::sync::LazyLock;
+use libfuzzer_sys::fuzz_target;
+use regex::Regex;
+
+use day03::interpreter::{Conditionals, Interpreter};
+use day03::scanner::{scan, ChunkedScanner, Instruction, Syntax, Token};
+
+// The original parser, restricted to ASCII digits like the puzzle
+static REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"mul\(([0-9]{1,3}),([0-9]{1,3})\)|do\(\)|don't\(\)").unwrap());
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(memory) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let tokens = scan(memory);
+    let expected: Vec<(Instruction, Range<usize>)> = REFERENCE.captures_iter(memory)
+        .map(|captures| {
+            let span = captures.get(0).unwrap().range();
+            let instruction = match (captures.get(1), captures.get(2)) {
+                (Some(x), Some(y)) => Instruction::mul(x.as_str().parse().unwrap(), y.as_str().parse().unwrap()),
+                _ if &memory[span.clone()] == "do()" => Instruction::Do,
+                _ => Instruction::Dont,
+            };
+            (instruction, span)
+        })
+        .collect();
+    let actual: Vec<(Instruction, Range<usize>)> = tokens.iter()
+        .map(|token| (token.instruction.clone(), token.span.clone()))
+        .collect();
+    assert_eq!(actual, expected);
+
+    // Reading the memory in small chunks finds the same instructions
+    let chunked: Vec<Token> = ChunkedScanner::with_chunk_size(data, Syntax::default(), 3)
+        .collect::<io::Result<_>>()
+        .unwrap();
+    assert_eq!(chunked, tokens);
+
+    // Products of 3 digit operands can not overflow
+    for conditionals in [Conditionals::Ignore, Conditionals::Honour] {
+        Interpreter::new().run(memory, conditionals).unwrap();
+    }
+});
@@ -0,0 +1,237 @@
+use std::fmt::Write;
+use std::ops::Range;
+use clap::ValueEnum;
+
+use crate::scanner::{Instruction, Scanner, Syntax};
+
+// How a part of the memory is marked in the annotated output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    // A call which is executed when do() and don't() are honoured
+    Enabled,
+    // A call which is skipped because it follows a don't()
+    Disabled,
+    // A do() or don't() instruction
+    Toggle,
+    // Something which starts like an instruction but is not one, like mul(4* or mul ( 2 , 4 )
+    NearMiss,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub mark: Mark,
+    pub span: Range<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AnnotateFormat {
+    // Memory with ANSI colours, for a terminal
+    Ansi,
+    // A standalone HTML page
+    Html,
+}
+
+// Find the instructions and near misses in the memory, in order of their position
+pub fn annotate(memory: &str, syntax: Syntax) -> Vec<Annotation> {
+    let bytes = memory.as_bytes();
+    let mut keywords: Vec<&str> = syntax.operation_names().chain(["don't", "do"]).collect();
+    keywords.sort_by_key(|keyword| std::cmp::Reverse(keyword.len()));
+    let tokens: Vec<_> = Scanner::with_syntax(memory, syntax.clone()).collect();
+
+    let mut annotations: Vec<Annotation> = Vec::new();
+    let mut enabled = true;
+    let mut tokens = tokens.into_iter().peekable();
+    let mut position = 0;
+    while position < bytes.len() {
+        let next_token_start = tokens.peek().map_or(bytes.len(), |token| token.span.start);
+        if position == next_token_start {
+            let token = tokens.next().unwrap();
+            let mark = match token.instruction {
+                Instruction::Do => {
+                    enabled = true;
+                    Mark::Toggle
+                }
+                Instruction::Dont => {
+                    enabled = false;
+                    Mark::Toggle
+                }
+                Instruction::Call(_) if enabled => Mark::Enabled,
+                Instruction::Call(_) => Mark::Disabled,
+            };
+            position = token.span.end;
+            annotations.push(Annotation { mark, span: token.span });
+        } else if let Some(keyword) = keywords.iter().find(|keyword| bytes[position..].starts_with(keyword.as_bytes())) {
+            let end = near_miss_end(bytes, position + keyword.len()).min(next_token_start);
+            annotations.push(Annotation { mark: Mark::NearMiss, span: position..end });
+            position = end;
+        } else {
+            position += 1;
+        }
+    }
+    annotations
+}
+
+// Extend a near miss past its keyword over anything which could be part of an instruction,
+// up to a closing parenthesis, and over the first punctuation which broke it, like the * in mul(4*
+fn near_miss_end(bytes: &[u8], mut position: usize) -> usize {
+    while let Some(&byte) = bytes.get(position) {
+        position += 1;
+        match byte {
+            b')' => return position,
+            b'(' | b',' | b' ' | b'0'..=b'9' => {}
+            byte if byte.is_ascii_punctuation() => return position,
+            _ => return position - 1,
+        }
+    }
+    position
+}
+
+// Render the memory with the annotated parts highlighted
+pub fn render(memory: &str, annotations: &[Annotation], format: AnnotateFormat) -> String {
+    let mut output = String::new();
+    if format == AnnotateFormat::Html {
+        output.push_str(HTML_HEADER);
+    }
+    let mut position = 0;
+    for annotation in annotations {
+        push_text(&mut output, &memory[position..annotation.span.start], format);
+        let text = &memory[annotation.span.clone()];
+        match format {
+            AnnotateFormat::Ansi => {
+                let _ = write!(output, "{}{}\x1b[0m", ansi_style(annotation.mark), text);
+            }
+            AnnotateFormat::Html => {
+                let _ = write!(output, "<span class=\"{}\">", html_class(annotation.mark));
+                push_text(&mut output, text, format);
+                output.push_str("</span>");
+            }
+        }
+        position = annotation.span.end;
+    }
+    push_text(&mut output, &memory[position..], format);
+    if format == AnnotateFormat::Html {
+        output.push_str(HTML_FOOTER);
+    }
+    output
+}
+
+const HTML_HEADER: &str = "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<style>
+pre { white-space: pre-wrap; word-break: break-all; color: #888; }
+.enabled { color: #080; font-weight: bold; }
+.disabled { color: #c00; text-decoration: line-through; }
+.toggle { color: #00c; font-weight: bold; }
+.near-miss { color: #a0a; text-decoration: underline wavy; }
+</style>
+</head>
+<body>
+<pre>";
+
+const HTML_FOOTER: &str = "</pre>
+</body>
+</html>
+";
+
+fn ansi_style(mark: Mark) -> &'static str {
+    match mark {
+        Mark::Enabled => "\x1b[1;32m",
+        Mark::Disabled => "\x1b[9;31m",
+        Mark::Toggle => "\x1b[1;34m",
+        Mark::NearMiss => "\x1b[4;35m",
+    }
+}
+
+fn html_class(mark: Mark) -> &'static str {
+    match mark {
+        Mark::Enabled => "enabled",
+        Mark::Disabled => "disabled",
+        Mark::Toggle => "toggle",
+        Mark::NearMiss => "near-miss",
+    }
+}
+
+// Append text outside of any markup, escaping it for HTML
+fn push_text(output: &mut String, text: &str, format: AnnotateFormat) {
+    if format == AnnotateFormat::Ansi {
+        output.push_str(text);
+        return;
+    }
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            c => output.push(c),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    const EXAMPLE: &str = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+    fn marked(memory: &str) -> Vec<(Mark, &str)> {
+        annotate(memory, Syntax::default()).into_iter()
+            .map(|annotation| (annotation.mark, &memory[annotation.span]))
+            .collect()
+    }
+
+    #[test]
+    fn test_annotate_example() {
+        assert_eq!(marked(EXAMPLE), vec![
+            (Mark::Enabled, "mul(2,4)"),
+            (Mark::NearMiss, "mul["),
+            (Mark::Toggle, "don't()"),
+            (Mark::Disabled, "mul(5,5)"),
+            (Mark::NearMiss, "mul(32,64]"),
+            (Mark::Disabled, "mul(11,8)"),
+            (Mark::Toggle, "do()"),
+            (Mark::Enabled, "mul(8,5)"),
+        ]);
+    }
+
+    #[test_case("mul(4*", "mul(4*")]
+    #[test_case("mul ( 2 , 4 )", "mul ( 2 , 4 )")]
+    #[test_case("xmul(1234,5)", "mul(1234,5)")]
+    #[test_case("don't", "don't")]
+    #[test_case("mulch", "mul")]
+    fn test_annotate_near_miss(memory: &str, expected: &str) {
+        assert_eq!(marked(memory), vec![(Mark::NearMiss, expected)]);
+    }
+
+    #[test]
+    fn test_near_miss_stops_at_instruction() {
+        assert_eq!(marked("mul(mul(2,3)"), vec![(Mark::NearMiss, "mul("), (Mark::Enabled, "mul(2,3)")]);
+    }
+
+    #[test]
+    fn test_render_html_escapes_memory() {
+        let memory = "<mul(2,4)&";
+        let html = render(memory, &annotate(memory, Syntax::default()), AnnotateFormat::Html);
+        assert!(html.contains("&lt;<span class=\"enabled\">mul(2,4)</span>&amp;"));
+    }
+
+    #[test]
+    fn test_render_ansi_keeps_memory() {
+        let rendered = render(EXAMPLE, &annotate(EXAMPLE, Syntax::default()), AnnotateFormat::Ansi);
+        let mut stripped = String::new();
+        let mut in_escape = false;
+        for c in rendered.chars() {
+            match c {
+                '\x1b' => in_escape = true,
+                'm' if in_escape => in_escape = false,
+                c if !in_escape => stripped.push(c),
+                _ => {}
+            }
+        }
+        assert_eq!(stripped, EXAMPLE);
+    }
+}
@@ -0,0 +1,271 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read};
+use std::ops::Range;
+
+use crate::scanner::{Call, ChunkedScanner, Instruction, Operand, Scanner, Syntax, Value, MAX_DIGITS};
+
+// State of the interpreter while executing instructions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct State {
+    // Whether calls are executed, toggled by do() and don't()
+    pub enabled: bool,
+    // Sum of the values of all executed calls
+    pub accumulator: Value,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State { enabled: true, accumulator: 0 }
+    }
+}
+
+// How do() and don't() affect the calls after them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conditionals {
+    // Every call is executed (part 1)
+    Ignore,
+    // Calls after don't() are skipped until the next do() (part 2)
+    Honour,
+}
+
+// Why an instruction could not be executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    // Its value, or the accumulator after adding its value, overflows
+    Overflow,
+    // It calls an operation which is not registered, or with the wrong number of operands,
+    // like a call scanned with a different syntax
+    UnknownOperation,
+}
+
+// An instruction which could not be executed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionError {
+    pub kind: ErrorKind,
+    pub instruction: Instruction,
+    pub span: Option<Range<usize>>,
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ErrorKind::Overflow => write!(f, "overflow executing {}", self.instruction)?,
+            ErrorKind::UnknownOperation => write!(f, "unknown operation executing {}", self.instruction)?,
+        }
+        if let Some(span) = &self.span {
+            write!(f, " at bytes {:?}", span)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ExecutionError {}
+
+// An operation which can be called from corrupted memory, like mul(x,y)
+pub trait Operation {
+    fn name(&self) -> &str;
+    fn arity(&self) -> usize;
+    // The value of the operation, or None if it overflows
+    fn apply(&self, operands: &[Value]) -> Option<Value>;
+}
+
+pub struct Mul;
+pub struct Add;
+pub struct Sub;
+
+impl Operation for Mul {
+    fn name(&self) -> &str {
+        "mul"
+    }
+    fn arity(&self) -> usize {
+        2
+    }
+    fn apply(&self, operands: &[Value]) -> Option<Value> {
+        operands[0].checked_mul(operands[1])
+    }
+}
+
+impl Operation for Add {
+    fn name(&self) -> &str {
+        "add"
+    }
+    fn arity(&self) -> usize {
+        2
+    }
+    fn apply(&self, operands: &[Value]) -> Option<Value> {
+        operands[0].checked_add(operands[1])
+    }
+}
+
+impl Operation for Sub {
+    fn name(&self) -> &str {
+        "sub"
+    }
+    fn arity(&self) -> usize {
+        2
+    }
+    fn apply(&self, operands: &[Value]) -> Option<Value> {
+        operands[0].checked_sub(operands[1])
+    }
+}
+
+// Executes the instructions found in corrupted memory
+pub struct Interpreter {
+    operations: Vec<Box<dyn Operation>>,
+    nesting: bool,
+    max_digits: usize,
+}
+
+// The puzzle interpreter only knows mul(x,y) with operands of 1 to 3 digits
+impl Default for Interpreter {
+    fn default() -> Self {
+        Interpreter { operations: vec![Box::new(Mul)], nesting: false, max_digits: 3 }
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Interpreter::default()
+    }
+
+    // Register an operation, replacing any operation with the same name
+    pub fn register<O: Operation + 'static>(mut self, operation: O) -> Self {
+        self.operations.retain(|existing| existing.name() != operation.name());
+        self.operations.push(Box::new(operation));
+        self
+    }
+
+    // Allow calls to be nested as operands of other calls, like mul(add(1,2),3)
+    pub fn allow_nesting(mut self, nesting: bool) -> Self {
+        self.nesting = nesting;
+        self
+    }
+
+    // Allow operands of 1 to max_digits digits, up to MAX_DIGITS
+    pub fn with_max_digits(mut self, max_digits: usize) -> Self {
+        assert!((1..=MAX_DIGITS).contains(&max_digits), "operands must have 1 to {} digits", MAX_DIGITS);
+        self.max_digits = max_digits;
+        self
+    }
+
+    // The syntax to scan memory with, knowing all registered operations
+    pub fn syntax(&self) -> Syntax {
+        let syntax = Syntax::new(self.nesting).with_max_digits(self.max_digits);
+        self.operations.iter().fold(syntax, |syntax, operation| {
+            syntax.with_operation(operation.name(), operation.arity())
+        })
+    }
+
+    // Scan the memory and execute all instructions found in it
+    pub fn run(&self, memory: &str, conditionals: Conditionals) -> Result<State, ExecutionError> {
+        let mut state = State::default();
+        for token in Scanner::with_syntax(memory, self.syntax()) {
+            self.execute(&token.instruction, &mut state, conditionals)
+                .map_err(|error| ExecutionError { span: Some(token.span), ..error })?;
+        }
+        Ok(state)
+    }
+
+    // Scan memory from a reader in chunks and execute all instructions found in it.
+    // An instruction which cannot be executed is returned as an error of kind InvalidData wrapping the ExecutionError.
+    pub fn run_reader<R: Read>(&self, reader: R, conditionals: Conditionals) -> io::Result<State> {
+        let mut state = State::default();
+        for token in ChunkedScanner::new(reader, self.syntax()) {
+            let token = token?;
+            self.execute(&token.instruction, &mut state, conditionals)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, ExecutionError { span: Some(token.span), ..error }))?;
+        }
+        Ok(state)
+    }
+
+    // Execute an instruction, leaving the state untouched if it cannot be executed
+    pub fn execute(&self, instruction: &Instruction, state: &mut State, conditionals: Conditionals) -> Result<(), ExecutionError> {
+        match instruction {
+            Instruction::Do | Instruction::Dont if conditionals == Conditionals::Ignore => {}
+            Instruction::Do => state.enabled = true,
+            Instruction::Dont => state.enabled = false,
+            Instruction::Call(call) if state.enabled => {
+                state.accumulator = self.evaluate(call)
+                    .and_then(|value| state.accumulator.checked_add(value).ok_or(ErrorKind::Overflow))
+                    .map_err(|kind| ExecutionError { kind, instruction: instruction.clone(), span: None })?;
+            }
+            Instruction::Call(_) => {}
+        }
+        Ok(())
+    }
+
+    // Evaluate a call, including any nested calls in its operands
+    pub fn evaluate(&self, call: &Call) -> Result<Value, ErrorKind> {
+        let operation = self.operations.iter()
+            .find(|operation| operation.name() == call.name && operation.arity() == call.operands.len())
+            .ok_or(ErrorKind::UnknownOperation)?;
+        let operands: Vec<Value> = call.operands.iter()
+            .map(|operand| match operand {
+                Operand::Number(number) => Ok(*number),
+                Operand::Call(call) => self.evaluate(call),
+            })
+            .collect::<Result<_, _>>()?;
+        operation.apply(&operands).ok_or(ErrorKind::Overflow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(Conditionals::Ignore, 161)]
+    #[test_case(Conditionals::Honour, 48)]
+    fn test_run(conditionals: Conditionals, expected: Value) {
+        let memory = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        assert_eq!(Interpreter::new().run(memory, conditionals).unwrap().accumulator, expected);
+    }
+
+    #[test_case(Conditionals::Ignore)]
+    #[test_case(Conditionals::Honour)]
+    fn test_run_reader_matches_run(conditionals: Conditionals) {
+        let memory = std::fs::read_to_string("memory.txt").unwrap();
+        let interpreter = Interpreter::new();
+        let state = interpreter.run_reader(memory.as_bytes(), conditionals).unwrap();
+        assert_eq!(state, interpreter.run(&memory, conditionals).unwrap());
+    }
+
+    #[test]
+    fn test_run_with_registered_operations() {
+        let interpreter = Interpreter::new().register(Add).register(Sub);
+        assert_eq!(interpreter.run("add(1,2)%sub(3,10)mul(2,2)", Conditionals::Honour).unwrap().accumulator, 3 - 7 + 4);
+    }
+
+    #[test]
+    fn test_run_nested() {
+        let interpreter = Interpreter::new().register(Add).allow_nesting(true);
+        assert_eq!(interpreter.run("mul(add(1,2),mul(3,4))", Conditionals::Honour).unwrap().accumulator, 36);
+    }
+
+    #[test]
+    fn test_run_wide_operands() {
+        let interpreter = Interpreter::new().with_max_digits(20);
+        let state = interpreter.run("mul(12345678901234567890,10)", Conditionals::Honour).unwrap();
+        assert_eq!(state.accumulator, 123456789012345678900);
+    }
+
+    #[test]
+    fn test_run_reports_overflow() {
+        let memory = "mul(2,3)..mul(99999999999999999999,99999999999999999999)";
+        let error = Interpreter::new().with_max_digits(20).run(memory, Conditionals::Honour).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Overflow);
+        assert_eq!(error.span, Some(10..56));
+    }
+
+    #[test_case("add", 2, "add(1,2)")]
+    #[test_case("mul", 3, "mul(1,2,3)")]
+    fn test_execute_unknown_operation(name: &str, arity: usize, memory: &str) {
+        let syntax = Syntax::default().with_operation(name, arity);
+        let token = Scanner::with_syntax(memory, syntax).next().unwrap();
+        let mut state = State::default();
+        let error = Interpreter::new().execute(&token.instruction, &mut state, Conditionals::Honour).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::UnknownOperation);
+        assert_eq!(state, State::default());
+    }
+}
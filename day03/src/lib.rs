@@ -0,0 +1,3 @@
+pub mod annotate;
+pub mod interpreter;
+pub mod scanner;
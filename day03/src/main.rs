@@ -1,54 +1,91 @@
-use regex::Regex;
-
-fn main() {
-    let memory = read_memory();
-    let multiplications = parse_multiplications(&memory);
-    let mut result = 0;
-    for multiplication in multiplications {
-        result += mul(multiplication);
-    }
-    println!("Result: {}", result);
-}
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use clap::{Parser, ValueEnum};
+
+use day03::annotate::{self, AnnotateFormat};
+use day03::interpreter::{Add, Conditionals, ExecutionError, Interpreter, State, Sub};
+use day03::scanner::{ChunkedScanner, MAX_DIGITS};
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// Corrupted memory to scan for instructions, or - to read from stdin
+    #[arg(long, default_value = "memory.txt")]
+    input: PathBuf,
+
+    /// Print each instruction with the byte span it was found at
+    #[arg(long)]
+    tokens: bool,
+
+    /// Extra operation to recognise besides mul, can be given multiple times
+    #[arg(long = "operation", value_enum)]
+    operations: Vec<ExtraOperation>,
+
+    /// Allow calls as operands of other calls, like mul(add(1,2),3)
+    #[arg(long)]
+    nested: bool,
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-struct Multiplication {
-    x: i32,
-    y: i32,
+    /// Largest number of digits an operand may have
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u8).range(1..=MAX_DIGITS as i64))]
+    max_digits: u8,
+
+    /// Print the memory with enabled and disabled calls, do()/don't() toggles and near misses highlighted
+    #[arg(long, value_enum)]
+    annotate: Option<AnnotateFormat>,
 }
 
-fn mul(multiplication: Multiplication) -> i32 {
-    multiplication.x * multiplication.y
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ExtraOperation {
+    Add,
+    Sub,
 }
 
+fn main() -> io::Result<()> {
+    let args = Args::parse();
 
-fn parse_multiplications(s: &str) -> Vec<Multiplication> {
-    let mut vec = Vec::new();
-    let mut enable_mul = true;
-    let re = Regex::new(r"mul\((\d{1,3}),(\d{1,3})\)|do\(\)|don't\(\)").unwrap();
+    let mut interpreter = Interpreter::new()
+        .allow_nesting(args.nested)
+        .with_max_digits(args.max_digits as usize);
+    for operation in &args.operations {
+        interpreter = match operation {
+            ExtraOperation::Add => interpreter.register(Add),
+            ExtraOperation::Sub => interpreter.register(Sub),
+        };
+    }
 
-    for cap in re.captures_iter(s) {
-        if cap.get(0).unwrap().as_str() == "do()" {
-            enable_mul = true;
-            continue;
-        }
-        if cap.get(0).unwrap().as_str() == "don't()" {
-            enable_mul = false;
-            continue;
+    if let Some(format) = args.annotate {
+        let mut memory = String::new();
+        open_memory(&args.input)?.read_to_string(&mut memory)?;
+        let annotations = annotate::annotate(&memory, interpreter.syntax());
+        print!("{}", annotate::render(&memory, &annotations, format));
+        return Ok(());
+    }
+
+    // Compute both answers in a single pass, so the memory can be streamed
+    let mut state_1 = State::default();
+    let mut state_2 = State::default();
+    for token in ChunkedScanner::new(open_memory(&args.input)?, interpreter.syntax()) {
+        let token = token?;
+        if args.tokens {
+            println!("{:?}\t{}", token.span, token.instruction);
         }
-        if !enable_mul {
-            continue;
+        for (state, conditionals) in [(&mut state_1, Conditionals::Ignore), (&mut state_2, Conditionals::Honour)] {
+            interpreter.execute(&token.instruction, state, conditionals).map_err(|error| {
+                io::Error::new(io::ErrorKind::InvalidData, ExecutionError { span: Some(token.span.clone()), ..error })
+            })?;
         }
-        let x: i32 = cap[1].parse().unwrap();
-        let y: i32 = cap[2].parse().unwrap();
-        vec.push(Multiplication { x, y });
     }
 
-    vec
+    println!("Answer 1: {}", state_1.accumulator);
+    println!("Answer 2: {}", state_2.accumulator);
+    Ok(())
 }
 
 
-// Read memory from the memory.txt file
-fn read_memory() -> String{
-    let memory: String = std::fs::read_to_string("memory.txt").unwrap();
-    memory
-}
\ No newline at end of file
+// Open the memory file, where - is stdin
+fn open_memory(path: &Path) -> io::Result<Box<dyn Read>> {
+    if path == Path::new("-") {
+        return Ok(Box::new(io::stdin().lock()));
+    }
+    Ok(Box::new(File::open(path)?))
+}
@@ -0,0 +1,425 @@
+use std::fmt;
+use std::io::{self, Read};
+use std::ops::Range;
+
+// Calls can be nested up to this depth when nesting is allowed
+const MAX_NESTING: usize = 8;
+
+// Number of bytes the chunked scanner reads at a time
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+// Longest number an operand can have in the puzzle, and at most
+const DEFAULT_MAX_DIGITS: usize = 3;
+pub const MAX_DIGITS: usize = 38;
+
+// Type of the numbers in instructions and of the values they evaluate to,
+// which fits any operand of up to MAX_DIGITS digits
+pub type Value = i128;
+
+// Instructions which can be found in corrupted memory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    // A call to an operation, like mul(2,4)
+    Call(Call),
+    Do,
+    Dont,
+}
+
+// A call to an operation with its operands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    pub name: String,
+    pub operands: Vec<Operand>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operand {
+    Number(Value),
+    // A nested call, only when the syntax allows nesting
+    Call(Call),
+}
+
+impl Instruction {
+    pub fn mul(x: Value, y: Value) -> Self {
+        Instruction::Call(Call {
+            name: "mul".to_string(),
+            operands: vec![Operand::Number(x), Operand::Number(y)],
+        })
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Call(call) => write!(f, "{}", call),
+            Instruction::Do => write!(f, "do()"),
+            Instruction::Dont => write!(f, "don't()"),
+        }
+    }
+}
+
+impl fmt::Display for Call {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(", self.name)?;
+        for (i, operand) in self.operands.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            match operand {
+                Operand::Number(number) => write!(f, "{}", number)?,
+                Operand::Call(call) => write!(f, "{}", call)?,
+            }
+        }
+        write!(f, ")")
+    }
+}
+
+// An instruction with the span of bytes it was found at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub instruction: Instruction,
+    pub span: Range<usize>,
+}
+
+// The operations the scanner recognises, by name and number of operands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Syntax {
+    operations: Vec<(String, usize)>,
+    nesting: bool,
+    max_digits: usize,
+}
+
+// The puzzle syntax only knows mul(x,y) with operands of 1 to 3 digits
+impl Default for Syntax {
+    fn default() -> Self {
+        Syntax::new(false).with_operation("mul", 2)
+    }
+}
+
+impl Syntax {
+    pub fn new(nesting: bool) -> Self {
+        Syntax { operations: Vec::new(), nesting, max_digits: DEFAULT_MAX_DIGITS }
+    }
+
+    // Allow operands of 1 to max_digits digits, up to MAX_DIGITS
+    pub fn with_max_digits(mut self, max_digits: usize) -> Self {
+        assert!((1..=MAX_DIGITS).contains(&max_digits), "operands must have 1 to {} digits", MAX_DIGITS);
+        self.max_digits = max_digits;
+        self
+    }
+
+    // Add an operation, replacing any operation with the same name
+    pub fn with_operation(mut self, name: &str, arity: usize) -> Self {
+        self.operations.retain(|(existing, _)| existing != name);
+        self.operations.push((name.to_string(), arity));
+        // Try longer names first, so an operation is not shadowed by one whose name is a prefix of its name
+        self.operations.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+        self
+    }
+
+    // Names of the recognised operations, longest first
+    pub fn operation_names(&self) -> impl Iterator<Item = &str> {
+        self.operations.iter().map(|(name, _)| name.as_str())
+    }
+
+    // The longest an instruction can be, so the scanner knows how far ahead it may need to look
+    pub fn max_instruction_length(&self) -> usize {
+        let max_depth = if self.nesting { MAX_NESTING } else { 0 };
+        let mut max_operand_length = self.max_digits;
+        let mut max_call_length = 0;
+        for _ in 0..=max_depth {
+            max_call_length = self.operations.iter()
+                .map(|(name, arity)| name.len() + 2 + arity.saturating_sub(1) + arity * max_operand_length)
+                .max()
+                .unwrap_or(0);
+            max_operand_length = max_operand_length.max(max_call_length);
+        }
+        max_call_length.max("don't()".len())
+    }
+}
+
+// Scans corrupted memory for instructions, skipping anything that does not form a complete instruction
+pub struct Scanner<'a> {
+    memory: &'a [u8],
+    position: usize,
+    syntax: Syntax,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(memory: &'a str) -> Self {
+        Scanner::with_syntax(memory, Syntax::default())
+    }
+
+    pub fn with_syntax(memory: &'a str, syntax: Syntax) -> Self {
+        Scanner { memory: memory.as_bytes(), position: 0, syntax }
+    }
+}
+
+impl Iterator for Scanner<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        while self.position < self.memory.len() {
+            let start = self.position;
+            if let Some((instruction, end)) = match_instruction(self.memory, start, &self.syntax) {
+                self.position = end;
+                return Some(Token { instruction, span: start..end });
+            }
+            self.position += 1;
+        }
+        None
+    }
+}
+
+// Scans corrupted memory from a reader in fixed-size chunks, in constant memory.
+// The scanner only tries to match an instruction at a position once it has read as far ahead as the longest
+// instruction could reach, so instructions split across chunks are found as well.
+pub struct ChunkedScanner<R> {
+    reader: R,
+    syntax: Syntax,
+    chunk_size: usize,
+    lookahead: usize,
+    buffer: Vec<u8>,
+    // Position in the buffer, and the offset of the start of the buffer in the memory
+    position: usize,
+    offset: usize,
+    eof: bool,
+}
+
+impl<R: Read> ChunkedScanner<R> {
+    pub fn new(reader: R, syntax: Syntax) -> Self {
+        ChunkedScanner::with_chunk_size(reader, syntax, DEFAULT_CHUNK_SIZE)
+    }
+
+    pub fn with_chunk_size(reader: R, syntax: Syntax, chunk_size: usize) -> Self {
+        let lookahead = syntax.max_instruction_length();
+        ChunkedScanner {
+            reader,
+            syntax,
+            chunk_size: chunk_size.max(1),
+            lookahead,
+            buffer: Vec::new(),
+            position: 0,
+            offset: 0,
+            eof: false,
+        }
+    }
+
+    // Read chunks until the buffer holds the lookahead past the position, or the reader is exhausted
+    fn fill(&mut self) -> io::Result<()> {
+        while !self.eof && self.buffer.len() - self.position < self.lookahead {
+            // Drop what has been scanned already
+            self.buffer.drain(..self.position);
+            self.offset += self.position;
+            self.position = 0;
+
+            let length = self.buffer.len();
+            self.buffer.resize(length + self.chunk_size, 0);
+            let n_read = loop {
+                match self.reader.read(&mut self.buffer[length..]) {
+                    Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                    result => break result,
+                }
+            };
+            let n_read = n_read.inspect_err(|_| self.buffer.truncate(length))?;
+            self.buffer.truncate(length + n_read);
+            self.eof = n_read == 0;
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for ChunkedScanner<R> {
+    type Item = io::Result<Token>;
+
+    fn next(&mut self) -> Option<io::Result<Token>> {
+        loop {
+            if let Err(error) = self.fill() {
+                return Some(Err(error));
+            }
+            if self.position >= self.buffer.len() {
+                return None;
+            }
+            let start = self.position;
+            if let Some((instruction, end)) = match_instruction(&self.buffer, start, &self.syntax) {
+                self.position = end;
+                let span = self.offset + start..self.offset + end;
+                return Some(Ok(Token { instruction, span }));
+            }
+            self.position += 1;
+        }
+    }
+}
+
+// Scan the memory for instructions
+pub fn scan(memory: &str) -> Vec<Token> {
+    Scanner::new(memory).collect()
+}
+
+// Match an instruction starting at the given position, returning it with the position just past it
+fn match_instruction(memory: &[u8], start: usize, syntax: &Syntax) -> Option<(Instruction, usize)> {
+    let rest = &memory[start..];
+    let (instruction, length) = if let Some(length) = match_literal(rest, b"do()") {
+        (Instruction::Do, length)
+    } else if let Some(length) = match_literal(rest, b"don't()") {
+        (Instruction::Dont, length)
+    } else {
+        let (call, length) = match_call(rest, syntax, 0)?;
+        (Instruction::Call(call), length)
+    };
+    Some((instruction, start + length))
+}
+
+fn match_literal(input: &[u8], literal: &[u8]) -> Option<usize> {
+    input.starts_with(literal).then_some(literal.len())
+}
+
+// Match a call like mul(x,y) to any operation of the syntax
+fn match_call(input: &[u8], syntax: &Syntax, depth: usize) -> Option<(Call, usize)> {
+    syntax.operations.iter().find_map(|(name, arity)| match_operation(input, name, *arity, syntax, depth))
+}
+
+// Match name(operand,...) with the given number of operands
+fn match_operation(input: &[u8], name: &str, arity: usize, syntax: &Syntax, depth: usize) -> Option<(Call, usize)> {
+    let mut position = match_literal(input, name.as_bytes())?;
+    position += match_literal(&input[position..], b"(")?;
+    let mut operands: Vec<Operand> = Vec::with_capacity(arity);
+    for i in 0..arity {
+        if i > 0 {
+            position += match_literal(&input[position..], b",")?;
+        }
+        let (operand, length) = match_operand(&input[position..], syntax, depth)?;
+        operands.push(operand);
+        position += length;
+    }
+    position += match_literal(&input[position..], b")")?;
+    Some((Call { name: name.to_string(), operands }, position))
+}
+
+fn match_operand(input: &[u8], syntax: &Syntax, depth: usize) -> Option<(Operand, usize)> {
+    if let Some((number, length)) = match_number(input, syntax.max_digits) {
+        return Some((Operand::Number(number), length));
+    }
+    if syntax.nesting && depth < MAX_NESTING {
+        let (call, length) = match_call(input, syntax, depth + 1)?;
+        return Some((Operand::Call(call), length));
+    }
+    None
+}
+
+// Match a number of 1 to max_digits digits
+fn match_number(input: &[u8], max_digits: usize) -> Option<(Value, usize)> {
+    let length = input.iter().take(max_digits).take_while(|byte| byte.is_ascii_digit()).count();
+    if length == 0 || input.get(length).is_some_and(u8::is_ascii_digit) {
+        return None;
+    }
+    let number = input[..length].iter().fold(0, |number, &digit| number * 10 + (digit - b'0') as Value);
+    Some((number, length))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test]
+    fn test_scan() {
+        let memory = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        let instructions: Vec<(Instruction, Range<usize>)> = scan(memory)
+            .into_iter()
+            .map(|token| (token.instruction, token.span))
+            .collect();
+        assert_eq!(instructions, vec![
+            (Instruction::mul(2, 4), 1..9),
+            (Instruction::Dont, 20..27),
+            (Instruction::mul(5, 5), 28..36),
+            (Instruction::mul(11, 8), 48..57),
+            (Instruction::Do, 59..63),
+            (Instruction::mul(8, 5), 64..72),
+        ]);
+    }
+
+    #[test_case("mul(4*"; "unfinished")]
+    #[test_case("mul(6,9!"; "wrong closing character")]
+    #[test_case("?(12,34)"; "missing name")]
+    #[test_case("mul ( 2 , 4 )"; "spaces")]
+    #[test_case("mul(1234,5)"; "too many digits")]
+    #[test_case("do ()"; "space in do")]
+    #[test_case("mul(5)"; "too few operands")]
+    fn test_scan_rejects(memory: &str) {
+        assert_eq!(scan(memory), vec![]);
+    }
+
+    #[test]
+    fn test_scan_restarts_after_partial_match() {
+        let tokens = scan("mul(1,mul(2,3)");
+        assert_eq!(tokens, vec![Token { instruction: Instruction::mul(2, 3), span: 6..14 }]);
+    }
+
+    #[test]
+    fn test_scan_without_nesting() {
+        let tokens = scan("mul(mul(2,3),4)");
+        assert_eq!(tokens, vec![Token { instruction: Instruction::mul(2, 3), span: 4..12 }]);
+    }
+
+    #[test]
+    fn test_scan_nested_calls() {
+        let syntax = Syntax::new(true).with_operation("mul", 2).with_operation("add", 2);
+        let tokens: Vec<Token> = Scanner::with_syntax("xmul(add(1,2),3)", syntax).collect();
+        let expected = Instruction::Call(Call {
+            name: "mul".to_string(),
+            operands: vec![
+                Operand::Call(Call { name: "add".to_string(), operands: vec![Operand::Number(1), Operand::Number(2)] }),
+                Operand::Number(3),
+            ],
+        });
+        assert_eq!(tokens, vec![Token { instruction: expected, span: 1..16 }]);
+    }
+
+    // The scanner finds the same instructions as the regex it replaced
+    #[test]
+    fn test_scan_matches_regex() {
+        let memory = std::fs::read_to_string("memory.txt").unwrap();
+        let re = regex::Regex::new(r"mul\((\d{1,3}),(\d{1,3})\)|do\(\)|don't\(\)").unwrap();
+        let expected: Vec<Range<usize>> = re.find_iter(&memory).map(|m| m.range()).collect();
+        let actual: Vec<Range<usize>> = scan(&memory).into_iter().map(|token| token.span).collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_chunked_scanner_matches_scanner() {
+        let memory = std::fs::read_to_string("memory.txt").unwrap();
+        let expected = scan(&memory);
+        for chunk_size in [1, 2, 3, 7, 64, 4096] {
+            let actual: Vec<Token> = ChunkedScanner::with_chunk_size(memory.as_bytes(), Syntax::default(), chunk_size)
+                .collect::<io::Result<_>>()
+                .unwrap();
+            assert_eq!(actual, expected, "chunk size {}", chunk_size);
+        }
+    }
+
+    #[test]
+    fn test_chunked_scanner_nested_calls_across_chunks() {
+        let syntax = Syntax::new(true).with_operation("mul", 2).with_operation("add", 2);
+        let memory = "..mul(add(1,2),mul(add(3,4),5))..don't()";
+        let expected: Vec<Token> = Scanner::with_syntax(memory, syntax.clone()).collect();
+        let actual: Vec<Token> = ChunkedScanner::with_chunk_size(memory.as_bytes(), syntax, 4)
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(actual, expected);
+        assert_eq!(actual.len(), 2);
+    }
+
+    #[test]
+    fn test_scan_with_max_digits() {
+        let syntax = Syntax::default().with_max_digits(5);
+        let tokens: Vec<Token> = Scanner::with_syntax("mul(12345,6)mul(123456,7)", syntax).collect();
+        assert_eq!(tokens, vec![Token { instruction: Instruction::mul(12345, 6), span: 0..12 }]);
+    }
+
+    #[test]
+    fn test_max_instruction_length() {
+        assert_eq!(Syntax::default().max_instruction_length(), "mul(123,456)".len());
+    }
+}
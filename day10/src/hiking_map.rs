@@ -0,0 +1,367 @@
+use std::collections::HashSet;
+use anyhow::Result;
+
+use crate::rules::TrailRules;
+use crate::topo_map::{Position, TopoMap};
+
+// Graph of the trails on a map, where the nodes are the points of the map
+// and each edge is a step along a trail to a neighbouring point, by default one higher
+#[derive(Debug, Clone)]
+pub struct TrailGraph {
+    // Nodes reachable in one step from each node
+    successors: Vec<Vec<usize>>,
+}
+
+impl TrailGraph {
+    pub fn new(topo_map: &TopoMap, rules: &TrailRules) -> Self {
+        let successors: Vec<Vec<usize>> = topo_map.positions()
+            .map(|position| {
+                let height = topo_map.height(position);
+                topo_map.neighbours(position, rules.neighbourhood)
+                    .filter(|&neighbour| rules.step_allowed(height, topo_map.height(neighbour)))
+                    .map(|neighbour| topo_map.index(neighbour))
+                    .collect()
+            })
+            .collect();
+        TrailGraph { successors }
+    }
+
+    pub fn successors(&self, node: usize) -> &[usize] {
+        &self.successors[node]
+    }
+
+    // All nodes reachable from a node, excluding the node itself
+    pub fn descendants(&self, node: usize) -> HashSet<usize> {
+        let mut descendants: HashSet<usize> = HashSet::new();
+        let mut stack: Vec<usize> = vec![node];
+        while let Some(node) = stack.pop() {
+            for &successor in self.successors(node) {
+                if descendants.insert(successor) {
+                    stack.push(successor);
+                }
+            }
+        }
+        descendants
+    }
+
+    // All paths from one node to another.
+    // Every edge changes the height in the same direction, so the graph has no cycles and every path is simple.
+    pub fn all_paths(&self, from: usize, to: usize) -> Vec<Vec<usize>> {
+        let mut paths: Vec<Vec<usize>> = Vec::new();
+        let mut path: Vec<usize> = vec![from];
+        self.extend_paths(&mut path, to, &mut paths);
+        paths
+    }
+
+    fn extend_paths(&self, path: &mut Vec<usize>, to: usize, paths: &mut Vec<Vec<usize>>) {
+        let node = *path.last().unwrap();
+        if node == to {
+            paths.push(path.clone());
+            return;
+        }
+        for &successor in self.successors(node) {
+            path.push(successor);
+            self.extend_paths(path, to, paths);
+            path.pop();
+        }
+    }
+}
+
+// A topographic map with the graph of its trails
+#[derive(Debug, Clone)]
+pub struct HikingMap {
+    pub topo_map: TopoMap,
+    pub rules: TrailRules,
+    pub graph: TrailGraph,
+}
+
+impl HikingMap {
+    // A hiking map with the puzzle rules
+    pub fn from_topo_map(topo_map: TopoMap) -> Self {
+        let rules = TrailRules::default();
+        let graph = TrailGraph::new(&topo_map, &rules);
+        HikingMap { topo_map, rules, graph }
+    }
+
+    // A hiking map with the given rules, which must let trails get from the start height to the end height
+    pub fn new(topo_map: TopoMap, rules: TrailRules) -> Result<Self> {
+        rules.validate()?;
+        let graph = TrailGraph::new(&topo_map, &rules);
+        Ok(HikingMap { topo_map, rules, graph })
+    }
+
+    // Positions at the start height, where trails start
+    pub fn trail_heads(&self) -> Vec<Position> {
+        self.topo_map.positions()
+            .filter(|&position| self.topo_map.height(position) == self.rules.start_height)
+            .collect()
+    }
+
+    // Positions at the end height which a trail head leads to, row by row
+    pub fn trail_ends(&self, trail_head: Position) -> Vec<Position> {
+        let mut trail_ends: Vec<Position> = self.graph.descendants(self.topo_map.index(trail_head))
+            .into_iter()
+            .map(|node| self.topo_map.position(node))
+            .filter(|&position| self.topo_map.height(position) == self.rules.end_height)
+            .collect();
+        trail_ends.sort();
+        trail_ends
+    }
+
+    // Number of hiking trails from each point to any trail end, by node.
+    // Every step along a trail goes one step closer to the end height, so visiting the points
+    // from the end height back finds the counts of all successors of a point before the point itself.
+    pub fn trail_counts(&self) -> Vec<usize> {
+        let n_steps = self.rules.n_steps();
+        let mut nodes_by_steps: Vec<Vec<usize>> = vec![Vec::new(); n_steps + 1];
+        for position in self.topo_map.positions() {
+            if let Some(steps) = self.rules.steps_to(self.topo_map.height(position)) {
+                nodes_by_steps[steps].push(self.topo_map.index(position));
+            }
+        }
+
+        let mut counts: Vec<usize> = vec![0; self.topo_map.n_rows() * self.topo_map.n_cols()];
+        for &node in &nodes_by_steps[n_steps] {
+            counts[node] = 1;
+        }
+        for nodes in nodes_by_steps[..n_steps].iter().rev() {
+            for &node in nodes {
+                counts[node] = self.graph.successors(node).iter().map(|&successor| counts[successor]).sum();
+            }
+        }
+        counts
+    }
+
+    // Steps along any hiking trail from a trail head, found from the trail counts without enumerating the trails
+    pub fn trail_steps(&self, trail_head: Position) -> Vec<(Position, Position)> {
+        let counts = self.trail_counts();
+        let start = self.topo_map.index(trail_head);
+        let mut steps: Vec<(Position, Position)> = Vec::new();
+        let mut visited: HashSet<usize> = HashSet::from([start]);
+        let mut stack: Vec<usize> = if counts[start] > 0 { vec![start] } else { Vec::new() };
+        while let Some(node) = stack.pop() {
+            for &successor in self.graph.successors(node) {
+                if counts[successor] == 0 {
+                    continue;
+                }
+                steps.push((self.topo_map.position(node), self.topo_map.position(successor)));
+                if visited.insert(successor) {
+                    stack.push(successor);
+                }
+            }
+        }
+        steps.sort();
+        steps
+    }
+
+    // All hiking trails from a trail head to any trail end it leads to.
+    // This enumerates every trail, so only use it when the trails themselves are needed.
+    pub fn hiking_trails(&self, trail_head: Position) -> Vec<Vec<Position>> {
+        let node = self.topo_map.index(trail_head);
+        self.trail_ends(trail_head).into_iter()
+            .flat_map(|trail_end| self.graph.all_paths(node, self.topo_map.index(trail_end)))
+            .map(|path| path.into_iter().map(|node| self.topo_map.position(node)).collect())
+            .collect()
+    }
+
+    // Number of trail ends a trail head leads to
+    pub fn score_trail_head(&self, trail_head: Position) -> usize {
+        self.trail_ends(trail_head).len()
+    }
+
+    pub fn score(&self) -> usize {
+        self.trail_heads().into_iter().map(|trail_head| self.score_trail_head(trail_head)).sum()
+    }
+
+    // Number of distinct hiking trails starting at a trail head
+    pub fn rate_trail_head(&self, trail_head: Position) -> usize {
+        self.trail_counts()[self.topo_map.index(trail_head)]
+    }
+
+    pub fn rating(&self) -> usize {
+        let counts = self.trail_counts();
+        self.trail_heads().into_iter().map(|trail_head| counts[self.topo_map.index(trail_head)]).sum()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Neighbourhood;
+    use test_case::test_case;
+
+    const EXAMPLE: &str = "\
+89010123
+78121874
+87430965
+96549874
+45678903
+32019012
+01329801
+10456732
+";
+
+    fn example() -> HikingMap {
+        HikingMap::from_topo_map(TopoMap::parse(EXAMPLE).unwrap())
+    }
+
+    #[test]
+    fn test_score() {
+        assert_eq!(example().score(), 36);
+    }
+
+    #[test]
+    fn test_rating() {
+        assert_eq!(example().rating(), 81);
+    }
+
+    #[test_case((0, 2), 5, 20)]
+    #[test_case((0, 4), 6, 24)]
+    #[test_case((6, 6), 3, 8)]
+    fn test_trail_head(trail_head: Position, score: usize, rating: usize) {
+        let hiking_map = example();
+        assert_eq!(hiking_map.score_trail_head(trail_head), score);
+        assert_eq!(hiking_map.rate_trail_head(trail_head), rating);
+    }
+
+    // The answers of the original Python implementation on the puzzle input
+    #[test]
+    fn test_puzzle_input_matches_python() {
+        let hiking_map = HikingMap::from_topo_map(TopoMap::from_file("map.txt").unwrap());
+        assert_eq!(hiking_map.score(), 822);
+        assert_eq!(hiking_map.rating(), 1801);
+    }
+
+    #[test]
+    fn test_rating_matches_enumerated_trails() {
+        let hiking_map = example();
+        for trail_head in hiking_map.trail_heads() {
+            assert_eq!(hiking_map.rate_trail_head(trail_head), hiking_map.hiking_trails(trail_head).len());
+        }
+    }
+
+    #[test]
+    fn test_rating_of_diagonal_slope() {
+        // The height of each point is the sum of its row and column, so every trail from the corner
+        // goes right or down at each step, and there are 2^9 trails to the diagonal at height 9
+        let rows: Vec<String> = (0..10)
+            .map(|i| (0..10).map(|j| char::from_digit((i + j).min(9), 10).unwrap()).collect())
+            .collect();
+        let hiking_map = HikingMap::from_topo_map(TopoMap::parse(&rows.join("\n")).unwrap());
+        assert_eq!(hiking_map.rate_trail_head((0, 0)), 512);
+    }
+
+    fn edges(hiking_map: &HikingMap) -> HashSet<(Position, Position)> {
+        let topo_map = &hiking_map.topo_map;
+        topo_map.positions()
+            .flat_map(|from| {
+                hiking_map.graph.successors(topo_map.index(from)).iter().map(move |&to| (from, topo_map.position(to)))
+            })
+            .collect()
+    }
+
+    // Edges built like the Python map_to_graph: each pair of points below and right of each other
+    // is visited once, adding an edge in whichever direction goes one up
+    fn python_edges(topo_map: &TopoMap) -> HashSet<(Position, Position)> {
+        let mut edges: HashSet<(Position, Position)> = HashSet::new();
+        for (i, j) in topo_map.positions() {
+            let height = topo_map.height((i, j));
+            for neighbour in [(i + 1, j), (i, j + 1)] {
+                if neighbour.0 >= topo_map.n_rows() || neighbour.1 >= topo_map.n_cols() {
+                    continue;
+                }
+                let neighbour_height = topo_map.height(neighbour);
+                if neighbour_height == height + 1 {
+                    edges.insert(((i, j), neighbour));
+                }
+                if neighbour_height + 1 == height {
+                    edges.insert((neighbour, (i, j)));
+                }
+            }
+        }
+        edges
+    }
+
+    #[test]
+    fn test_edges_join_neighbours() {
+        // Every edge joins points which are next to each other, so each is in the other's neighbourhood
+        for (from, to) in edges(&example()) {
+            assert_eq!(from.0.abs_diff(to.0) + from.1.abs_diff(to.1), 1, "{:?} -> {:?}", from, to);
+        }
+    }
+
+    #[test]
+    fn test_edges_match_python_graph() {
+        for topo_map in [TopoMap::parse(EXAMPLE).unwrap(), TopoMap::from_file("map.txt").unwrap()] {
+            let expected = python_edges(&topo_map);
+            assert_eq!(edges(&HikingMap::from_topo_map(topo_map)), expected);
+        }
+    }
+
+    // Trail heads in corners and on edges, whose trails leave the head upwards or leftwards
+    #[test_case("9876543210", (0, 9), 1, 1)]
+    #[test_case("0\n1\n2\n3\n4\n5\n6\n7\n8\n9", (0, 0), 1, 1)]
+    #[test_case("9\n8\n7\n6\n5\n4\n3\n2\n1\n0", (9, 0), 1, 1)]
+    #[test_case("9876543210\n8987654321", (0, 9), 2, 10)]
+    #[test_case("1234\n0565\n9876", (1, 0), 1, 2)]
+    fn test_edge_trail_heads(contents: &str, trail_head: Position, score: usize, rating: usize) {
+        let hiking_map = HikingMap::from_topo_map(TopoMap::parse(contents).unwrap());
+        assert_eq!(hiking_map.trail_heads(), vec![trail_head]);
+        assert_eq!(hiking_map.score_trail_head(trail_head), score);
+        assert_eq!(hiking_map.rate_trail_head(trail_head), rating);
+    }
+
+    #[test_case(TrailRules { start_height: 9, end_height: 0, step: -1, ..TrailRules::default() }, 36, 81)]
+    #[test_case(TrailRules { neighbourhood: Neighbourhood::Eight, ..TrailRules::default() }, 43, 93)]
+    #[test_case(TrailRules { start_height: 1, end_height: 9, step: 2, ..TrailRules::default() }, 0, 0)]
+    #[test_case(TrailRules { start_height: 3, end_height: 6, ..TrailRules::default() }, 7, 9)]
+    fn test_rules(rules: TrailRules, score: usize, rating: usize) {
+        let hiking_map = HikingMap::new(TopoMap::parse(EXAMPLE).unwrap(), rules).unwrap();
+        assert_eq!(hiking_map.score(), score);
+        assert_eq!(hiking_map.rating(), rating);
+        let n_trails: usize = hiking_map.trail_heads().into_iter()
+            .map(|trail_head| hiking_map.hiking_trails(trail_head).len())
+            .sum();
+        assert_eq!(n_trails, rating);
+    }
+
+    #[test_case(TrailRules { step: -1, ..TrailRules::default() })]
+    #[test_case(TrailRules { step: 0, ..TrailRules::default() })]
+    #[test_case(TrailRules { end_height: 12, ..TrailRules::default() })]
+    fn test_invalid_rules(rules: TrailRules) {
+        assert!(HikingMap::new(TopoMap::parse(EXAMPLE).unwrap(), rules).is_err());
+    }
+
+    #[test]
+    fn test_rules_with_larger_steps() {
+        let hiking_map = HikingMap::new(
+            TopoMap::parse("1357\n3579\n").unwrap(),
+            TrailRules { start_height: 1, end_height: 9, step: 2, ..TrailRules::default() },
+        ).unwrap();
+        assert_eq!(hiking_map.score(), 1);
+        assert_eq!(hiking_map.rating(), 4);
+    }
+
+    #[test]
+    fn test_trail_steps_match_enumerated_trails() {
+        let hiking_map = example();
+        for trail_head in hiking_map.trail_heads() {
+            let mut expected: Vec<(Position, Position)> = hiking_map.hiking_trails(trail_head).iter()
+                .flat_map(|trail| trail.windows(2).map(|step| (step[0], step[1])))
+                .collect();
+            expected.sort();
+            expected.dedup();
+            assert_eq!(hiking_map.trail_steps(trail_head), expected);
+        }
+    }
+
+    #[test]
+    fn test_hiking_trails_climb_one_step_at_a_time() {
+        let hiking_map = HikingMap::from_topo_map(TopoMap::parse("0123\n1234\n5456\n6789\n").unwrap());
+        for trail in hiking_map.hiking_trails((0, 0)) {
+            let heights: Vec<u8> = trail.iter().map(|&position| hiking_map.topo_map.height(position)).collect();
+            assert_eq!(heights, (0..=9).collect::<Vec<u8>>());
+        }
+    }
+}
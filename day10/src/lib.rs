@@ -0,0 +1,4 @@
+pub mod hiking_map;
+pub mod render;
+pub mod rules;
+pub mod topo_map;
@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+
+use day10::hiking_map::HikingMap;
+use day10::render::{render, RenderFormat};
+use day10::rules::{Neighbourhood, TrailRules};
+use day10::topo_map::{Position, TopoMap};
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// Topographic map with a row of single digit heights on each line
+    #[arg(long, default_value = "map.txt")]
+    input: PathBuf,
+
+    /// Print the positions of the trail heads
+    #[arg(long)]
+    trail_heads: bool,
+
+    /// Height of the trail heads
+    #[arg(long, default_value_t = 0)]
+    start_height: u8,
+
+    /// Height of the trail ends
+    #[arg(long, default_value_t = 9)]
+    end_height: u8,
+
+    /// Difference in height of every step along a trail, negative for trails going down
+    #[arg(long, default_value_t = 1, allow_hyphen_values = true)]
+    step: i8,
+
+    /// Points a trail can step to, with or without the diagonals
+    #[arg(long, value_enum, default_value_t = Neighbourhood::Four)]
+    neighbourhood: Neighbourhood,
+
+    /// Draw the map with the score of each trail head in place of its height
+    #[arg(long, value_enum)]
+    render: Option<RenderFormat>,
+
+    /// Trail head whose trails to highlight in the drawing, as <row>,<column>
+    #[arg(long, value_parser = parse_position, requires = "render")]
+    highlight: Option<Position>,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let rules = TrailRules {
+        start_height: args.start_height,
+        end_height: args.end_height,
+        step: args.step,
+        neighbourhood: args.neighbourhood,
+    };
+    let hiking_map = HikingMap::new(TopoMap::from_file(&args.input)?, rules)?;
+
+    if let Some(format) = args.render {
+        if let Some(trail_head) = args.highlight {
+            if !hiking_map.trail_heads().contains(&trail_head) {
+                bail!("there is no trail head at {:?}", trail_head);
+            }
+        }
+        print!("{}", render(&hiking_map, args.highlight, format));
+        return Ok(());
+    }
+
+    if args.trail_heads {
+        println!("Trail heads: {:?}", hiking_map.trail_heads());
+    }
+    println!("Score: {}", hiking_map.score());
+    println!("Rating: {}", hiking_map.rating());
+
+    Ok(())
+}
+
+// Parse a position given as <row>,<column>
+fn parse_position(s: &str) -> Result<Position> {
+    let (row, column) = s.split_once(',').context("expected <row>,<column>")?;
+    Ok((row.trim().parse()?, column.trim().parse()?))
+}
@@ -0,0 +1,172 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use clap::ValueEnum;
+
+use crate::hiking_map::HikingMap;
+use crate::topo_map::Position;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RenderFormat {
+    // Text with ANSI colours, for a terminal
+    Ansi,
+    // A standalone SVG image
+    Svg,
+}
+
+// Size of a point of the map in the SVG image, in pixels
+const CELL_SIZE: usize = 20;
+
+// Draw the map with the score of each trail head in place of its height,
+// highlighting the trails from the given trail head if any
+pub fn render(hiking_map: &HikingMap, highlight: Option<Position>, format: RenderFormat) -> String {
+    let scores: HashMap<Position, usize> = hiking_map.trail_heads().into_iter()
+        .map(|trail_head| (trail_head, hiking_map.score_trail_head(trail_head)))
+        .collect();
+    let steps = highlight.map_or_else(Vec::new, |trail_head| hiking_map.trail_steps(trail_head));
+    match format {
+        RenderFormat::Ansi => render_ansi(hiking_map, &scores, highlight, &steps),
+        RenderFormat::Svg => render_svg(hiking_map, &scores, highlight, &steps),
+    }
+}
+
+fn render_ansi(
+    hiking_map: &HikingMap,
+    scores: &HashMap<Position, usize>,
+    highlight: Option<Position>,
+    steps: &[(Position, Position)],
+) -> String {
+    let topo_map = &hiking_map.topo_map;
+    let on_trail: HashSet<Position> = steps.iter().flat_map(|&(from, to)| [from, to]).collect();
+    let width = scores.values().map(|score| score.to_string().len()).max().unwrap_or(1);
+
+    let mut output = String::new();
+    for i in 0..topo_map.n_rows() {
+        for j in 0..topo_map.n_cols() {
+            let position = (i, j);
+            let height = topo_map.height(position);
+            let (text, style) = match scores.get(&position) {
+                Some(score) if Some(position) == highlight => (score.to_string(), "\x1b[1;7;33m"),
+                Some(score) => (score.to_string(), "\x1b[1;33m"),
+                None if on_trail.contains(&position) && height == hiking_map.rules.end_height => {
+                    (height.to_string(), "\x1b[1;31m")
+                }
+                None if on_trail.contains(&position) => (height.to_string(), "\x1b[1;32m"),
+                None => (height.to_string(), "\x1b[2m"),
+            };
+            if j > 0 {
+                output.push(' ');
+            }
+            let _ = write!(output, "{}{:>width$}\x1b[0m", style, text, width = width);
+        }
+        output.push('\n');
+    }
+    output
+}
+
+fn render_svg(
+    hiking_map: &HikingMap,
+    scores: &HashMap<Position, usize>,
+    highlight: Option<Position>,
+    steps: &[(Position, Position)],
+) -> String {
+    let topo_map = &hiking_map.topo_map;
+    let (width, height) = (topo_map.n_cols() * CELL_SIZE, topo_map.n_rows() * CELL_SIZE);
+    let centre = |(i, j): Position| (j * CELL_SIZE + CELL_SIZE / 2, i * CELL_SIZE + CELL_SIZE / 2);
+
+    let mut output = String::new();
+    let _ = writeln!(
+        output,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" \
+         font-family=\"monospace\" font-size=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">",
+        width, height, width, height, CELL_SIZE * 3 / 5
+    );
+
+    // The terrain, from dark green valleys to light brown peaks
+    for position in topo_map.positions() {
+        let (i, j) = position;
+        let height = topo_map.height(position) as usize;
+        let _ = writeln!(
+            output,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"hsl({}, 40%, {}%)\"/>",
+            j * CELL_SIZE, i * CELL_SIZE, CELL_SIZE, CELL_SIZE, 120 - height * 10, 25 + height * 6
+        );
+    }
+
+    // The trails from the highlighted trail head, under the labels
+    for &(from, to) in steps {
+        let ((x1, y1), (x2, y2)) = (centre(from), centre(to));
+        let _ = writeln!(
+            output,
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"crimson\" stroke-width=\"4\" stroke-linecap=\"round\" opacity=\"0.8\"/>",
+            x1, y1, x2, y2
+        );
+    }
+
+    // The height of each point, or the score of each trail head on a marker
+    for position in topo_map.positions() {
+        let (x, y) = centre(position);
+        match scores.get(&position) {
+            Some(score) => {
+                let stroke = if Some(position) == highlight { "crimson" } else { "black" };
+                let _ = writeln!(
+                    output,
+                    "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"gold\" stroke=\"{}\" stroke-width=\"2\"/>",
+                    x, y, CELL_SIZE / 2 - 1, stroke
+                );
+                let _ = writeln!(output, "<text x=\"{}\" y=\"{}\" font-weight=\"bold\">{}</text>", x, y, score);
+            }
+            None => {
+                let _ = writeln!(
+                    output,
+                    "<text x=\"{}\" y=\"{}\" fill=\"white\" opacity=\"0.6\">{}</text>",
+                    x, y, topo_map.height(position)
+                );
+            }
+        }
+    }
+
+    output.push_str("</svg>\n");
+    output
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::topo_map::TopoMap;
+
+    fn hiking_map() -> HikingMap {
+        HikingMap::from_topo_map(TopoMap::parse("0123\n1234\n8765\n9876\n").unwrap())
+    }
+
+    #[test]
+    fn test_render_ansi_overlays_scores() {
+        let hiking_map = HikingMap::from_topo_map(TopoMap::parse("01234\n98765\n").unwrap());
+        assert_eq!(
+            render(&hiking_map, None, RenderFormat::Ansi),
+            "\x1b[1;33m1\x1b[0m \x1b[2m1\x1b[0m \x1b[2m2\x1b[0m \x1b[2m3\x1b[0m \x1b[2m4\x1b[0m\n\
+             \x1b[2m9\x1b[0m \x1b[2m8\x1b[0m \x1b[2m7\x1b[0m \x1b[2m6\x1b[0m \x1b[2m5\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_render_ansi_highlights_trails() {
+        let hiking_map = HikingMap::from_topo_map(TopoMap::parse("01234\n98765\n").unwrap());
+        assert_eq!(
+            render(&hiking_map, Some((0, 0)), RenderFormat::Ansi),
+            "\x1b[1;7;33m1\x1b[0m \x1b[1;32m1\x1b[0m \x1b[1;32m2\x1b[0m \x1b[1;32m3\x1b[0m \x1b[1;32m4\x1b[0m\n\
+             \x1b[1;31m9\x1b[0m \x1b[1;32m8\x1b[0m \x1b[1;32m7\x1b[0m \x1b[1;32m6\x1b[0m \x1b[1;32m5\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let hiking_map = hiking_map();
+        let rendered = render(&hiking_map, Some((0, 0)), RenderFormat::Svg);
+        assert!(rendered.starts_with("<svg "));
+        assert!(rendered.ends_with("</svg>\n"));
+        assert_eq!(rendered.matches("<rect ").count(), 16);
+        assert_eq!(rendered.matches("<line ").count(), hiking_map.trail_steps((0, 0)).len());
+        assert_eq!(rendered.matches("<circle ").count(), 1);
+    }
+}
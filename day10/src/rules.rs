@@ -0,0 +1,93 @@
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+
+// Points which a trail can step to from a point
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Neighbourhood {
+    // The points above, left, right and below
+    Four,
+    // The points around, including the diagonals
+    Eight,
+}
+
+// Rules which a hiking trail follows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrailRules {
+    // Height of the trail heads, where trails start
+    pub start_height: u8,
+    // Height of the trail ends, where trails stop
+    pub end_height: u8,
+    // Difference in height of every step along a trail, negative for trails going down
+    pub step: i8,
+    pub neighbourhood: Neighbourhood,
+}
+
+// The puzzle rules: trails climb from 0 to 9 one step at a time, without diagonal moves
+impl Default for TrailRules {
+    fn default() -> Self {
+        TrailRules { start_height: 0, end_height: 9, step: 1, neighbourhood: Neighbourhood::Four }
+    }
+}
+
+impl TrailRules {
+    // Check that trails can get from the start height to the end height in steps of the given size
+    pub fn validate(&self) -> Result<()> {
+        if self.start_height > 9 || self.end_height > 9 {
+            bail!("invalid heights {} to {}, heights are single digits", self.start_height, self.end_height);
+        }
+        if self.step == 0 {
+            bail!("step must not be 0");
+        }
+        let climb = self.end_height as i32 - self.start_height as i32;
+        if climb % self.step as i32 != 0 || climb / (self.step as i32) < 0 {
+            bail!("can not get from height {} to {} in steps of {}", self.start_height, self.end_height, self.step);
+        }
+        Ok(())
+    }
+
+    // Number of steps along every trail
+    pub fn n_steps(&self) -> usize {
+        ((self.end_height as i32 - self.start_height as i32) / self.step as i32) as usize
+    }
+
+    // Number of steps from the start height to a height, if a trail can pass through that height
+    pub fn steps_to(&self, height: u8) -> Option<usize> {
+        let climb = height as i32 - self.start_height as i32;
+        let steps = climb / self.step as i32;
+        (climb % self.step as i32 == 0 && (0..=self.n_steps() as i32).contains(&steps)).then_some(steps as usize)
+    }
+
+    // Whether a trail can step from one height to the next
+    pub fn step_allowed(&self, from: u8, to: u8) -> bool {
+        to as i32 - from as i32 == self.step as i32
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(0, 9, 1, true)]
+    #[test_case(9, 0, -1, true)]
+    #[test_case(1, 9, 2, true)]
+    #[test_case(0, 9, 2, false)]
+    #[test_case(0, 9, -1, false)]
+    #[test_case(0, 9, 0, false)]
+    #[test_case(0, 10, 1, false)]
+    fn test_validate(start_height: u8, end_height: u8, step: i8, expected: bool) {
+        let rules = TrailRules { start_height, end_height, step, ..TrailRules::default() };
+        assert_eq!(rules.validate().is_ok(), expected);
+    }
+
+    #[test_case(0, None)]
+    #[test_case(1, Some(0))]
+    #[test_case(2, None)]
+    #[test_case(3, Some(1))]
+    #[test_case(9, Some(4))]
+    fn test_steps_to(height: u8, expected: Option<usize>) {
+        let rules = TrailRules { start_height: 1, end_height: 9, step: 2, ..TrailRules::default() };
+        assert_eq!(rules.steps_to(height), expected);
+    }
+}
@@ -0,0 +1,132 @@
+use std::path::Path;
+use anyhow::{bail, Context, Result};
+
+use crate::rules::Neighbourhood;
+
+// Position of a point on the map as (row, column)
+pub type Position = (usize, usize);
+
+// Topographic map with the height of each point, stored row by row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopoMap {
+    heights: Vec<u8>,
+    n_rows: usize,
+    n_cols: usize,
+}
+
+impl TopoMap {
+    // Read a topographic map from a file with a row of single digit heights on each line
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("could not read map {}", path.display()))?;
+        TopoMap::parse(&contents).with_context(|| format!("could not parse map {}", path.display()))
+    }
+
+    // Parse a topographic map with a row of single digit heights on each line.
+    // Every row must have the same number of heights, but there may be more or fewer rows than columns.
+    pub fn parse(contents: &str) -> Result<Self> {
+        let mut heights: Vec<u8> = Vec::new();
+        let mut n_rows = 0;
+        let mut n_cols = 0;
+        for (line_number, line) in contents.lines().enumerate().map(|(i, line)| (i + 1, line.trim())) {
+            for (column, c) in line.chars().enumerate() {
+                let Some(height) = c.to_digit(10) else {
+                    bail!("invalid height '{}' at line {}, column {}", c, line_number, column + 1);
+                };
+                heights.push(height as u8);
+            }
+            let row_length = heights.len() - n_rows * n_cols;
+            if n_rows == 0 {
+                n_cols = row_length;
+            } else if row_length != n_cols {
+                bail!("line {} has {} heights, but the lines before have {}", line_number, row_length, n_cols);
+            }
+            n_rows += 1;
+        }
+        if n_rows == 0 || n_cols == 0 {
+            bail!("map is empty");
+        }
+        Ok(TopoMap { heights, n_rows, n_cols })
+    }
+
+    pub fn n_rows(&self) -> usize {
+        self.n_rows
+    }
+
+    pub fn n_cols(&self) -> usize {
+        self.n_cols
+    }
+
+    pub fn height(&self, (i, j): Position) -> u8 {
+        self.heights[self.index((i, j))]
+    }
+
+    // Index of a position in the heights, which is also its node in the trail graph
+    pub fn index(&self, (i, j): Position) -> usize {
+        i * self.n_cols + j
+    }
+
+    pub fn position(&self, index: usize) -> Position {
+        (index / self.n_cols, index % self.n_cols)
+    }
+
+    // Positions in the neighbourhood of a position which are on the map, row by row
+    pub fn neighbours(&self, (i, j): Position, neighbourhood: Neighbourhood) -> impl Iterator<Item = Position> + '_ {
+        let offsets: &'static [(isize, isize)] = match neighbourhood {
+            Neighbourhood::Four => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Neighbourhood::Eight => &[(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)],
+        };
+        offsets.iter().filter_map(move |&(di, dj)| {
+            let i = i.checked_add_signed(di).filter(|&i| i < self.n_rows)?;
+            let j = j.checked_add_signed(dj).filter(|&j| j < self.n_cols)?;
+            Some((i, j))
+        })
+    }
+
+    // All positions on the map, row by row
+    pub fn positions(&self) -> impl Iterator<Item = Position> + '_ {
+        (0..self.n_rows).flat_map(move |i| (0..self.n_cols).map(move |j| (i, j)))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test]
+    fn test_parse() {
+        let topo_map = TopoMap::parse("0123\n1234\n").unwrap();
+        assert_eq!((topo_map.n_rows(), topo_map.n_cols()), (2, 4));
+        assert_eq!(topo_map.height((1, 3)), 4);
+        assert_eq!(topo_map.position(topo_map.index((1, 2))), (1, 2));
+    }
+
+    #[test]
+    fn test_parse_non_square() {
+        let topo_map = TopoMap::parse("012\n345\n678\n901\n").unwrap();
+        assert_eq!((topo_map.n_rows(), topo_map.n_cols()), (4, 3));
+        assert_eq!(topo_map.position(10), (3, 1));
+        assert_eq!(topo_map.height((3, 1)), 0);
+    }
+
+    #[test_case("01.3\n", "invalid height '.' at line 1, column 3")]
+    #[test_case("0123\n123\n", "line 2 has 3 heights, but the lines before have 4")]
+    #[test_case("0123\n\n1234\n", "line 2 has 0 heights, but the lines before have 4")]
+    #[test_case("", "map is empty")]
+    fn test_parse_invalid(contents: &str, expected: &str) {
+        assert_eq!(TopoMap::parse(contents).unwrap_err().to_string(), expected);
+    }
+
+    #[test_case((0, 0), Neighbourhood::Four, vec![(0, 1), (1, 0)])]
+    #[test_case((1, 2), Neighbourhood::Four, vec![(0, 2), (1, 1)])]
+    #[test_case((1, 1), Neighbourhood::Four, vec![(0, 1), (1, 0), (1, 2)])]
+    #[test_case((0, 0), Neighbourhood::Eight, vec![(0, 1), (1, 0), (1, 1)])]
+    #[test_case((1, 1), Neighbourhood::Eight, vec![(0, 0), (0, 1), (0, 2), (1, 0), (1, 2)])]
+    fn test_neighbours(position: Position, neighbourhood: Neighbourhood, expected: Vec<Position>) {
+        let topo_map = TopoMap::parse("012\n345\n").unwrap();
+        assert_eq!(topo_map.neighbours(position, neighbourhood).collect::<Vec<_>>(), expected);
+    }
+}
@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+use anyhow::{Context, Result};
+use clap::{Parser, ValueEnum};
+
+use day11::memo::StoneCounter;
+use day11::stones::{count_stones_by_number, parse_stones, simulate};
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// Numbers engraved on the stones in a line
+    #[arg(long, default_value = "puzzle_input.txt")]
+    input: PathBuf,
+
+    /// Number of times to blink, can be given multiple times [default: 25 and 75, only 25 for naive]
+    #[arg(long = "blinks")]
+    blinks: Vec<usize>,
+
+    /// How to count the stones
+    #[arg(long, value_enum, default_value_t = Method::Memo)]
+    method: Method,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Method {
+    // Simulate every stone, only feasible for few blinks
+    Naive,
+    // Count the stones carrying each number, blink by blink
+    CountMap,
+    // Count the stones each stone turns into, remembering the counts, with exact counts for any number of blinks
+    Memo,
+}
+
+impl Method {
+    // Blink counts of the puzzle which the method can count, the naive line of stones for 75 blinks does not fit in memory
+    fn default_blinks(&self) -> Vec<usize> {
+        match self {
+            Method::Naive => vec![25],
+            Method::CountMap | Method::Memo => vec![25, 75],
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let mut args = Args::parse();
+    if args.blinks.is_empty() {
+        args.blinks = args.method.default_blinks();
+    }
+    let contents = std::fs::read_to_string(&args.input)
+        .with_context(|| format!("could not read stones {}", args.input.display()))?;
+    let stones = parse_stones(&contents)?;
+
+    // Shared between the blink counts, so later counts reuse the earlier ones
+    let mut counter = StoneCounter::new();
+    for &blinks in &args.blinks {
+        let n_stones = match args.method {
+            Method::Naive => simulate(&stones, blinks)?.len().to_string(),
+            Method::CountMap => count_stones_by_number(&stones, blinks)?.to_string(),
+            Method::Memo => counter.count_line(&stones, blinks)?.to_string(),
+        };
+        println!("Stones after {} blinks: {}", blinks, n_stones);
+    }
+
+    Ok(())
+}
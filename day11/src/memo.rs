@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use anyhow::{Context, Result};
+use num_bigint::BigUint;
+
+use crate::stones::blink_stone;
+
+// Counts the stones a single stone turns into, remembering the counts it answered.
+// Only a few thousand distinct numbers appear however long the stones blink, so the numbers are interned
+// and the counts of every number are moved on blink by blink, keeping only those for the last number of blinks.
+// Memory therefore stays proportional to the number of distinct numbers, whatever the number of blinks.
+// The counts grow exponentially with the blinks and outgrow a u128 after a few hundred, so they are exact big integers.
+// Counts are only remembered by the counter itself, so callers keep one counter for all their questions.
+#[derive(Debug, Default)]
+pub struct StoneCounter {
+    // Index of each number which appeared
+    ids: HashMap<u64, usize>,
+    // Indices of the numbers each number turns into after one blink, or None if it is too large to blink
+    children: Vec<Option<(usize, Option<usize>)>>,
+    // Number of blinks the counts are for
+    blinks: usize,
+    // Number of stones each number turns into after that many blinks,
+    // or None if it turns into a number too large to blink before then
+    counts: Vec<Option<BigUint>>,
+    // Counts already answered, kept when the counts move on to more blinks
+    answers: HashMap<(u64, usize), BigUint>,
+}
+
+impl StoneCounter {
+    pub fn new() -> Self {
+        StoneCounter::default()
+    }
+
+    // Number of stones a stone turns into after blinking,
+    // which fails if the stone turns into a number too large to blink within those blinks.
+    // Fewer blinks than the counts are for start the counts over, unless they were answered before.
+    pub fn count_stones(&mut self, stone: u64, blinks: usize) -> Result<BigUint> {
+        if let Some(count) = self.answers.get(&(stone, blinks)) {
+            return Ok(count.clone());
+        }
+        let id = self.intern(stone);
+        if blinks < self.blinks {
+            self.restart();
+        }
+        while self.blinks < blinks {
+            self.blink();
+        }
+        let count = self.counts[id].clone().with_context(|| {
+            format!("stone {} turns into a stone too large to multiply by 2024 within {} blinks", stone, blinks)
+        })?;
+        self.answers.insert((stone, blinks), count.clone());
+        Ok(count)
+    }
+
+    // Number of stones a line of stones turns into after blinking
+    pub fn count_line(&mut self, stones: &[u64], blinks: usize) -> Result<BigUint> {
+        // Every stone is interned first, so no new number starts the counts over halfway through the line
+        for &stone in stones {
+            self.intern(stone);
+        }
+        stones.iter().map(|&stone| self.count_stones(stone, blinks)).sum()
+    }
+
+    // Number of counts remembered, for the last number of blinks and answered before
+    pub fn len(&self) -> usize {
+        self.counts.len() + self.answers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty() && self.answers.is_empty()
+    }
+
+    // Index of a number, interning it and every number it turns into.
+    // A number too large to blink is interned without children, and only fails a count which blinks it.
+    // New numbers have no counts for the blinks so far, so the counts start over.
+    fn intern(&mut self, stone: u64) -> usize {
+        if let Some(&id) = self.ids.get(&stone) {
+            return id;
+        }
+        let first_new_id = self.children.len();
+        let mut new_stones: Vec<u64> = vec![stone];
+        self.ids.insert(stone, first_new_id);
+        while first_new_id + new_stones.len() > self.children.len() {
+            let children = blink_stone(new_stones[self.children.len() - first_new_id]).ok().map(|(left, right)| {
+                let mut id_of = |stone: u64| {
+                    let next_id = first_new_id + new_stones.len();
+                    *self.ids.entry(stone).or_insert_with(|| {
+                        new_stones.push(stone);
+                        next_id
+                    })
+                };
+                (id_of(left), right.map(id_of))
+            });
+            self.children.push(children);
+        }
+        self.restart();
+        first_new_id
+    }
+
+    // Go back to every number being a single stone before blinking
+    fn restart(&mut self) {
+        self.blinks = 0;
+        self.counts = vec![Some(BigUint::from(1u8)); self.children.len()];
+    }
+
+    // Move the counts on by one blink, from the counts of the children of each number
+    fn blink(&mut self) {
+        self.counts = self.children.iter()
+            .map(|children| match *children {
+                None => None,
+                Some((left, None)) => self.counts[left].clone(),
+                Some((left, Some(right))) => Some(self.counts[left].as_ref()? + self.counts[right].as_ref()?),
+            })
+            .collect();
+        self.blinks += 1;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+    use crate::stones::{count_stones_by_number, simulate};
+
+    #[test_case(125, 6, 7)]
+    #[test_case(17, 6, 15)]
+    #[test_case(0, 0, 1)]
+    #[test_case(0, 4, 4)]
+    fn test_count_stones(stone: u64, blinks: usize, expected: u32) {
+        assert_eq!(StoneCounter::new().count_stones(stone, blinks).unwrap(), BigUint::from(expected));
+        assert_eq!(simulate(&[stone], blinks).unwrap().len() as u32, expected);
+    }
+
+    #[test]
+    fn test_count_line_example() {
+        let mut counter = StoneCounter::new();
+        assert_eq!(counter.count_line(&[125, 17], 25).unwrap(), BigUint::from(55312u32));
+        assert_eq!(counter.count_line(&[125, 17], 75).unwrap(), BigUint::from(count_stones_by_number(&[125, 17], 75).unwrap()));
+    }
+
+    #[test]
+    fn test_counter_remembers_counts() {
+        let mut counter = StoneCounter::new();
+        assert_eq!(counter.count_stones(125, 10).unwrap(), BigUint::from(simulate(&[125], 10).unwrap().len()));
+        assert_eq!(counter.count_stones(125, 20).unwrap(), BigUint::from(simulate(&[125], 20).unwrap().len()));
+        // An answered count is remembered after the counts moved on
+        assert_eq!(counter.count_stones(125, 10).unwrap(), BigUint::from(simulate(&[125], 10).unwrap().len()));
+        assert_eq!(counter.blinks, 20);
+        // Fewer blinks which were not answered start the counts over
+        assert_eq!(counter.count_stones(125, 5).unwrap(), BigUint::from(5u8));
+        assert_eq!(counter.blinks, 5);
+        // And so does a new number
+        assert_eq!(counter.count_stones(17, 6).unwrap(), BigUint::from(15u8));
+        assert_eq!(counter.count_stones(17, 10).unwrap(), BigUint::from(simulate(&[17], 10).unwrap().len()));
+    }
+
+    #[test]
+    fn test_large_odd_digit_stone() {
+        let stone = 12345678901234567;
+        let mut counter = StoneCounter::new();
+        // The stone only fails once it blinks, like the other methods
+        assert_eq!(counter.count_stones(stone, 0).unwrap(), BigUint::from(1u8));
+        assert_eq!(count_stones_by_number(&[stone], 0).unwrap(), 1);
+        assert_eq!(simulate(&[stone], 0).unwrap().len(), 1);
+        assert!(counter.count_stones(stone, 1).is_err());
+        assert!(counter.count_line(&[125, stone], 5).is_err());
+        assert_eq!(counter.count_stones(125, 5).unwrap(), BigUint::from(5u8));
+    }
+
+    #[test]
+    fn test_large_stone_reached_after_blinks() {
+        // 5000000000000 becomes 10120000000000000 after one blink, which is too large to multiply by 2024
+        let mut counter = StoneCounter::new();
+        assert_eq!(counter.count_stones(5000000000000, 1).unwrap(), BigUint::from(1u8));
+        assert_eq!(count_stones_by_number(&[5000000000000], 1).unwrap(), 1);
+        assert!(counter.count_stones(5000000000000, 2).is_err());
+        assert!(count_stones_by_number(&[5000000000000], 2).is_err());
+    }
+
+    #[test]
+    fn test_count_stones_beyond_u128() {
+        let mut counter = StoneCounter::new();
+        let count = counter.count_stones(0, 1000).unwrap();
+        assert!(count > BigUint::from(u128::MAX));
+        // Blinking once turns 0 into 1, so the count for 1 is the count for 0 one blink later
+        assert_eq!(counter.count_stones(1, 999).unwrap(), count);
+    }
+
+    #[test]
+    fn test_memory_does_not_grow_with_blinks() {
+        let mut counter = StoneCounter::new();
+        counter.count_line(&[125, 17], 10).unwrap();
+        let len = counter.len();
+        counter.count_line(&[125, 17], 1000).unwrap();
+        // Only the counts for the last number of blinks are kept, besides the two new answers
+        assert_eq!(counter.len(), len + 2);
+        assert_eq!(counter.counts.len(), counter.children.len());
+    }
+}
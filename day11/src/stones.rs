@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use anyhow::{bail, Context, Result};
+
+// Parse the engraved numbers of a line of stones separated by whitespace
+pub fn parse_stones(s: &str) -> Result<Vec<u64>> {
+    s.split_whitespace()
+        .map(|stone| stone.parse().with_context(|| format!("invalid stone '{}'", stone)))
+        .collect()
+}
+
+// The stones a stone changes into when blinking:
+// 0 becomes 1, a number with an even number of digits splits into its left and right halves,
+// and any other number is multiplied by 2024, which fails if the product does not fit a u64
+pub fn blink_stone(stone: u64) -> Result<(u64, Option<u64>)> {
+    if stone == 0 {
+        return Ok((1, None));
+    }
+    let n_digits = stone.ilog10() + 1;
+    if n_digits.is_multiple_of(2) {
+        let half = 10u64.pow(n_digits / 2);
+        return Ok((stone / half, Some(stone % half)));
+    }
+    match stone.checked_mul(2024) {
+        Some(product) => Ok((product, None)),
+        None => bail!("stone {} is too large to multiply by 2024", stone),
+    }
+}
+
+// Simulate every stone, keeping the order of the line. The line grows exponentially, so only for few blinks.
+pub fn simulate(stones: &[u64], blinks: usize) -> Result<Vec<u64>> {
+    let mut stones = stones.to_vec();
+    for _ in 0..blinks {
+        let mut next: Vec<u64> = Vec::with_capacity(stones.len() * 2);
+        for &stone in &stones {
+            let (left, right) = blink_stone(stone)?;
+            next.push(left);
+            next.extend(right);
+        }
+        stones = next;
+    }
+    Ok(stones)
+}
+
+// Count the stones after blinking by tracking how many stones carry each number.
+// The order of the stones does not change what they become, and only a few thousand distinct numbers appear.
+// Fails once the number of stones no longer fits a u64, after about a hundred blinks.
+pub fn count_stones_by_number(stones: &[u64], blinks: usize) -> Result<u64> {
+    let mut counts: HashMap<u64, u64> = HashMap::new();
+    for &stone in stones {
+        *counts.entry(stone).or_default() += 1;
+    }
+    for _ in 0..blinks {
+        let mut next: HashMap<u64, u64> = HashMap::with_capacity(counts.len());
+        for (stone, count) in counts {
+            let (left, right) = blink_stone(stone)?;
+            for child in std::iter::once(left).chain(right) {
+                let child_count = next.entry(child).or_default();
+                *child_count = child_count.checked_add(count).context("number of stones does not fit a u64")?;
+            }
+        }
+        counts = next;
+    }
+    counts.values().try_fold(0u64, |total, &count| total.checked_add(count))
+        .context("number of stones does not fit a u64")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(0, (1, None))]
+    #[test_case(1, (2024, None))]
+    #[test_case(10, (1, Some(0)))]
+    #[test_case(99, (9, Some(9)))]
+    #[test_case(1000, (10, Some(0)))]
+    #[test_case(999, (2021976, None))]
+    fn test_blink_stone(stone: u64, expected: (u64, Option<u64>)) {
+        assert_eq!(blink_stone(stone).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_large_odd_digit_stone_overflows() {
+        let stones = parse_stones("12345678901234567").unwrap();
+        assert_eq!(
+            blink_stone(stones[0]).unwrap_err().to_string(),
+            "stone 12345678901234567 is too large to multiply by 2024"
+        );
+        assert!(simulate(&stones, 3).is_err());
+        assert!(count_stones_by_number(&stones, 3).is_err());
+        // A larger stone which splits in half is fine
+        assert_eq!(count_stones_by_number(&[1234567890123456], 3).unwrap(), 8);
+    }
+
+    #[test]
+    fn test_count_stones_by_number_overflows() {
+        assert!(count_stones_by_number(&[125, 17], 200).is_err());
+    }
+
+    #[test]
+    fn test_simulate_example() {
+        assert_eq!(simulate(&[0, 1, 10, 99, 999], 1).unwrap(), vec![1, 2024, 1, 0, 9, 9, 2021976]);
+        assert_eq!(simulate(&[125, 17], 6).unwrap(), parse_stones(
+            "2097446912 14168 4048 2 0 2 4 40 48 2024 40 48 80 96 2 8 6 7 6 0 3 2"
+        ).unwrap());
+    }
+
+    #[test_case(6, 22)]
+    #[test_case(25, 55312)]
+    fn test_count_stones_example(blinks: usize, expected: u64) {
+        assert_eq!(simulate(&[125, 17], blinks).unwrap().len() as u64, expected);
+        assert_eq!(count_stones_by_number(&[125, 17], blinks).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_stones() {
+        assert_eq!(parse_stones("28 4 3179\n").unwrap(), vec![28, 4, 3179]);
+        assert!(parse_stones("28 x").is_err());
+    }
+}